@@ -14,7 +14,7 @@ static SAMPLE_FREQ: f32 = 1_000.0;
 
 
 fn generate_sine(n : usize) -> f32 {
-    let gen = SineGen::new(220.0);
+    let mut gen = SineGen::new(220.0);
     let mut s = 0.0;
     for i in 0..(n*SAMPLE_FREQ as usize) {
         s += gen.sample(i as f32 / SAMPLE_FREQ);
@@ -44,9 +44,13 @@ fn parse_params() -> Params {
 /// Create Signal generator based on given params
 fn create_generator(params: &Params) -> Box<SignalGen + 'static> {
     match params.gen_name.as_ref() {
+        "cosine"    => Box::new(CosineGen::new(params.freq)),
         "triangle"  => Box::new(TriangleGen::new(params.freq)),
-        "square"    => Box::new(SquareGen::new(params.freq)),
+        "sawtooth"  => Box::new(SawtoothGen::new(params.freq)),
+        "square"    => Box::new(SquareGen::new(params.freq, 0.5)),
         "noise"     => Box::new(NoiseGen::new(0.4)),
+        "white"     => Box::new(WhiteNoiseGen::new(1)),
+        "pink"      => Box::new(PinkNoiseGen::new(1)),
         "chirp"     => Box::new(ChirpGen::new(1.0, 50.0, 1.0)),
         _           => Box::new(SineGen::new(params.freq)),
     }
@@ -0,0 +1,124 @@
+//! Buffers for streaming processing
+//!
+
+use crate::RealBuffer;
+
+
+/// Fixed capacity FIFO buffer for overlapping frames.
+/// When the buffer is full, pushing new samples drops the oldest ones.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::buffers::RingBuffer;
+/// 
+/// let mut buffer = RingBuffer::new(8);
+/// buffer.push_frame(&vec![1.0, 2.0, 3.0, 4.0]);
+/// assert_eq!(buffer.read(3, 2), vec![1.0, 2.0, 3.0]);
+/// assert_eq!(buffer.available(), 2);
+/// buffer.push_frame(&vec![5.0, 6.0]);
+/// assert_eq!(buffer.read(3, 2), vec![3.0, 4.0, 5.0]);
+/// ```
+pub struct RingBuffer {
+    data: RealBuffer,
+    // Position of the oldest sample
+    start: usize,
+    count: usize,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> RingBuffer {
+        assert!(capacity > 0, "Capacity must be greater than 0");
+        RingBuffer { data: vec![0.0; capacity], start: 0, count: 0 }
+    }
+
+    /// Maximum number of stored samples
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Number of samples which can be read
+    pub fn available(&self) -> usize {
+        self.count
+    }
+
+    /// Append samples. If there is not enough space, the oldest samples are overwritten
+    pub fn push_frame(&mut self, frame: &RealBuffer) {
+        let capacity = self.data.len();
+        for &x in frame {
+            if self.count < capacity {
+                self.data[(self.start + self.count) % capacity] = x;
+                self.count += 1;
+            } else {
+                self.data[self.start] = x;
+                self.start = (self.start + 1) % capacity;
+            }
+        }
+    }
+
+    /// Return len oldest samples and then drop hop samples from the buffer.
+    /// With hop < len consecutive reads return overlapping windows.
+    /// Panics if there are less than len samples available.
+    pub fn read(&mut self, len: usize, hop: usize) -> RealBuffer {
+        assert!(len <= self.count, "Not enough samples in the buffer");
+        let capacity = self.data.len();
+        let output = (0..len).map(|i| self.data[(self.start + i) % capacity]).collect();
+        let hop = usize::min(hop, self.count);
+        self.start = (self.start + hop) % capacity;
+        self.count -= hop;
+        output
+    }
+
+    /// Remove all samples
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.count = 0;
+    }
+}
+
+
+/// ------------------------------------------------------------------------------------------------
+/// Module unit tests
+/// ------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping_windows() {
+        let mut buffer = RingBuffer::new(6);
+        let mut windows = vec![];
+        for frame in (0..12).map(|i| i as f32).collect::<RealBuffer>().chunks(3) {
+            buffer.push_frame(&frame.to_vec());
+            while buffer.available() >= 4 {
+                windows.push(buffer.read(4, 2));
+            }
+        }
+        assert_eq!(windows, vec![
+            vec![0.0, 1.0, 2.0, 3.0],
+            vec![2.0, 3.0, 4.0, 5.0],
+            vec![4.0, 5.0, 6.0, 7.0],
+            vec![6.0, 7.0, 8.0, 9.0],
+            vec![8.0, 9.0, 10.0, 11.0],
+        ]);
+        assert_eq!(buffer.available(), 2);
+    }
+
+    #[test]
+    fn test_overwrite_oldest() {
+        let mut buffer = RingBuffer::new(4);
+        buffer.push_frame(&vec![1.0, 2.0, 3.0]);
+        buffer.push_frame(&vec![4.0, 5.0, 6.0]);
+        assert_eq!(buffer.available(), 4);
+        assert_eq!(buffer.read(4, 4), vec![3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(buffer.available(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_read_too_much() {
+        let mut buffer = RingBuffer::new(4);
+        buffer.push_frame(&vec![1.0]);
+        buffer.read(2, 1);
+    }
+}
@@ -1,24 +1,28 @@
 //! Analyze discrete signal in frequency domain
+//!
+//! FFT can be calculated for any size. Power of 2 sizes are the fastest.
+//! Other sizes are factorized (mixed radix) and prime sizes use Rader's algorithm.
 use std::sync::Arc;
 use rustfft::{FFTplanner, FFT};
-use crate::num_complex::Complex32;
-use crate::{ComplexBuffer, RealBuffer, ProcessingNode};
+use crate::num_complex::{Complex, Complex32, Complex64};
+use crate::{ComplexBuffer, RealBuffer, ProcessingNode, Sample};
 
 
-pub struct ForwardFFT {
-    fft: Arc<FFT<f32>>,
+/// FFT of complex buffer. Generic over sample type (f32 or f64)
+pub struct ForwardFFT<T: Sample = f32> {
+    fft: Arc<dyn FFT<T>>,
 }
 
-pub struct InverseFFT {
-    fft: Arc<FFT<f32>>,
+/// Inverse FFT of complex buffer (not normalized). Generic over sample type (f32 or f64)
+pub struct InverseFFT<T: Sample = f32> {
+    fft: Arc<dyn FFT<T>>,
 }
 
-impl ForwardFFT {
+impl<T: Sample> ForwardFFT<T> {
     /// Define new transformation
     /// ## Params:
-    ///   * sample_rate - Samples per second (1/sample_frequency)
-    ///   * sample_size - Size of the vector which will be converter. Should be power of 2 (or 3)
-    pub fn new(sample_size: usize) -> ForwardFFT {
+    ///   * sample_size - Size of the vector which will be converted. Can be any size
+    pub fn new(sample_size: usize) -> ForwardFFT<T> {
         let mut fft = FFTplanner::new(false);
         ForwardFFT {
             fft: fft.plan_fft(sample_size),
@@ -26,29 +30,28 @@ impl ForwardFFT {
     }
 
     /// Forward DFT (implemented as FFT)
-    pub fn process(&mut self, mut input: &mut ComplexBuffer, mut output: &mut ComplexBuffer) {
-        self.fft.process(&mut input, &mut output);
+    pub fn process(&mut self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.fft.process(input, output);
     }
 }
 
-impl InverseFFT {
+impl<T: Sample> InverseFFT<T> {
     /// Define new transformation
     /// ## Params:
-    ///   * sample_size - Size of the vector which will be converter. Should be power of 2 (or 3)
-    pub fn new(sample_size: usize) -> InverseFFT {
+    ///   * sample_size - Size of the vector which will be converted. Can be any size
+    pub fn new(sample_size: usize) -> InverseFFT<T> {
         let mut fft = FFTplanner::new(true);
         InverseFFT {
             fft: fft.plan_fft(sample_size),
         }
     }
 
-    /// Forward DFT (implemented as FFT)
-    pub fn process(&mut self, mut input: &mut ComplexBuffer, mut output: &mut ComplexBuffer) {
-        self.fft.process(&mut input, &mut output);
+    /// Inverse DFT (implemented as FFT)
+    pub fn process(&mut self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.fft.process(input, output);
     }
 }
 
-
 pub struct ForwardFFTNode {
     fft: Arc<FFT<f32>>,
     input_complex: ComplexBuffer,
@@ -80,6 +83,8 @@ impl ProcessingNode for ForwardFFTNode {
 }
 
 
+/// Inverse FFT which returns only real part of the signal.
+/// The output is not normalized (it is scaled by the FFT size).
 pub struct InverseFFTNode {
     fft: Arc<FFT<f32>>,
     input_complex: ComplexBuffer,
@@ -116,13 +121,393 @@ impl ProcessingNode for InverseFFTNode {
 }
 
 
+/// Forward FFT with complex input.
+/// FFT plan and buffers are created once, so processing frames doesn't allocate memory.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::num_complex::Complex32;
+/// use dsp::fft::ComplexForwardFFTNode;
+/// 
+/// let signal = vec![Complex32::new(1.0, 0.0), Complex32::new(0.0, 0.0)];
+/// let mut fft = ComplexForwardFFTNode::new(2);
+/// assert_eq!(fft.process(&signal), &vec![Complex32::new(1.0, 0.0); 2]);
+/// ```
+pub struct ComplexForwardFFTNode {
+    fft: Arc<dyn FFT<f32>>,
+    input_complex: ComplexBuffer,
+    output: ComplexBuffer,
+}
+
+impl ComplexForwardFFTNode {
+    pub fn new(size: usize) -> ComplexForwardFFTNode {
+        let mut fft = FFTplanner::new(false);
+        let fft = fft.plan_fft(size);
+        let input_complex = vec![Complex32::new(0.0, 0.0); size];
+        let output = vec![Complex32::new(0.0, 0.0); size];
+        ComplexForwardFFTNode {fft, input_complex, output}
+    }
+}
+
+impl ProcessingNode for ComplexForwardFFTNode {
+    type InBuffer = ComplexBuffer;
+    type OutBuffer = ComplexBuffer;
+    
+    fn process(&mut self, input: &ComplexBuffer) -> &ComplexBuffer {
+        // FFT uses input buffer as a scratch space, so the data has to be copied first
+        let n = usize::min(input.len(), self.input_complex.len());
+        self.input_complex[..n].copy_from_slice(&input[..n]);
+        self.fft.process(&mut self.input_complex, &mut self.output);
+        &self.output
+    }
+}
+
+
+/// Inverse FFT with complex output.
+/// Calculated with forward FFT as: ifft(x) = conj(fft(conj(x))) / N
+/// Output is normalized by 1/N, so ifft(fft(x)) == x
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::ProcessingNode;
+/// use dsp::num_complex::Complex32;
+/// use dsp::fft::ComplexInverseFFTNode;
+/// 
+/// let spectrum = vec![Complex32::new(4.0, 0.0); 4];
+/// let mut ifft = ComplexInverseFFTNode::new(4);
+/// let output = ifft.process(&spectrum);
+/// assert_approx_eq!(output[0].re, 4.0, 1e-5f32);
+/// assert_approx_eq!(output[1].re, 0.0, 1e-5f32);
+/// ```
+pub struct ComplexInverseFFTNode {
+    fft: Arc<dyn FFT<f32>>,
+    input_complex: ComplexBuffer,
+    output: ComplexBuffer,
+}
+
+impl ComplexInverseFFTNode {
+    pub fn new(size: usize) -> ComplexInverseFFTNode {
+        let mut fft = FFTplanner::new(false);
+        let fft = fft.plan_fft(size);
+        let input_complex = vec![Complex32::new(0.0, 0.0); size];
+        let output = vec![Complex32::new(0.0, 0.0); size];
+        ComplexInverseFFTNode {fft, input_complex, output}
+    }
+}
+
+impl ProcessingNode for ComplexInverseFFTNode {
+    type InBuffer = ComplexBuffer;
+    type OutBuffer = ComplexBuffer;
+    
+    fn process(&mut self, input: &ComplexBuffer) -> &ComplexBuffer {
+        for (c, x) in self.input_complex.iter_mut().zip(input) {
+            *c = x.conj();
+        }
+        self.fft.process(&mut self.input_complex, &mut self.output);
+        let scale = 1.0 / self.output.len() as f32;
+        for c in self.output.iter_mut() {
+            *c = c.conj() * scale;
+        }
+        &self.output
+    }
+}
+
+
+/// In-place forward FFT (radix-2 Cooley-Tukey).
+/// Uses only the input buffer plus constant extra memory.
+/// The samples are first reordered with bit-reversal permutation: sample at index i is swapped
+/// with the sample at the index which has bits of i in reverse order (e.g. 001 <-> 100 for N=8).
+/// Then log2(N) butterfly stages combine DFTs of sizes 2, 4, ..., N.
+/// 
+/// Panics if the buffer length is not a power of 2.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::fft;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let mut buf = vec![Complex32::new(1.0, 0.0), Complex32::new(0.0, 0.0)];
+/// fft::fft_in_place(&mut buf);
+/// assert_eq!(buf, vec![Complex32::new(1.0, 0.0); 2]);
+/// ```
+pub fn fft_in_place(buf: &mut ComplexBuffer) {
+    let n = buf.len();
+    if n <= 1 {
+        return;
+    }
+    assert!(n.is_power_of_two(), "In-place FFT requires power of 2 size");
+
+    // Bit-reversal permutation
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+
+    // Butterflies
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f64::consts::PI / len as f64;
+        let w_len = Complex64::new(angle.cos(), angle.sin());
+        for start in (0..n).step_by(len) {
+            let mut w = Complex64::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let twiddle = Complex32::new(w.re as f32, w.im as f32);
+                let u = buf[start + k];
+                let v = buf[start + k + len / 2] * twiddle;
+                buf[start + k] = u + v;
+                buf[start + k + len / 2] = u - v;
+                w *= w_len;
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// FFT of the real signal.
+/// Spectrum of the real signal is conjugate symmetric, so only bins 0..=N/2 are returned.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::fft;
+/// 
+/// let spectrum = fft::rfft(&vec![1.0, 0.0, 0.0, 0.0]);
+/// assert_eq!(spectrum.len(), 3);
+/// assert_eq!(spectrum[2].re, 1.0);
+/// ```
+pub fn rfft(input: &RealBuffer) -> ComplexBuffer {
+    let size = input.len();
+    let mut input_complex: ComplexBuffer = input.iter().map(|&v| Complex32::new(v, 0.0)).collect();
+    let mut output = vec![Complex32::new(0.0, 0.0); size];
+    ForwardFFT::new(size).process(&mut input_complex, &mut output);
+    output.truncate(size / 2 + 1);
+    output
+}
+
+/// Reconstruct real signal from the spectrum returned by rfft.
+/// Output is normalized, so irfft(rfft(x), x.len()) == x
+///   * spectrum - bins 0..=N/2
+///   * size - Length N of the signal. Required since it can be odd.
+pub fn irfft(spectrum: &ComplexBuffer, size: usize) -> RealBuffer {
+    let mut input = vec![Complex32::new(0.0, 0.0); size];
+    for i in 0..usize::min(spectrum.len(), size / 2 + 1) {
+        input[i] = spectrum[i];
+        if i > 0 {
+            input[size - i] = spectrum[i].conj();
+        }
+    }
+    let mut output = vec![Complex32::new(0.0, 0.0); size];
+    InverseFFT::new(size).process(&mut input, &mut output);
+    output.iter().map(|c| c.re / size as f32).collect()
+}
+
+
+/// 2D FFT of the matrix stored in row-major order.
+/// 1D FFT is applied to each row and then to each column.
+///   * data - Matrix with rows*cols elements
+///   * rows - Number of rows
+///   * cols - Number of columns
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::fft;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let data = vec![Complex32::new(1.0, 0.0); 4];
+/// let spectrum = fft::fft2(&data, 2, 2);
+/// assert_eq!(spectrum[0].re, 4.0);
+/// assert_eq!(spectrum[3].re, 0.0);
+/// ```
+pub fn fft2(data: &ComplexBuffer, rows: usize, cols: usize) -> ComplexBuffer {
+    transform2(data, rows, cols, false)
+}
+
+/// Inverse 2D FFT. Output is normalized, so ifft2(fft2(x)) == x
+pub fn ifft2(data: &ComplexBuffer, rows: usize, cols: usize) -> ComplexBuffer {
+    let scale = 1.0 / (rows * cols) as f32;
+    transform2(data, rows, cols, true).iter().map(|c| c * scale).collect()
+}
+
+fn transform2(data: &ComplexBuffer, rows: usize, cols: usize, inverse: bool) -> ComplexBuffer {
+    assert_eq!(data.len(), rows * cols);
+    let mut planner = FFTplanner::new(inverse);
+    let mut output = data.clone();
+
+    // Rows
+    let row_fft = planner.plan_fft(cols);
+    let mut input = vec![Complex32::new(0.0, 0.0); cols];
+    for row in output.chunks_mut(cols) {
+        input.copy_from_slice(row);
+        row_fft.process(&mut input, row);
+    }
+
+    // Columns
+    let col_fft = planner.plan_fft(rows);
+    let mut input = vec![Complex32::new(0.0, 0.0); rows];
+    let mut column = vec![Complex32::new(0.0, 0.0); rows];
+    for c in 0..cols {
+        for r in 0..rows {
+            input[r] = output[r * cols + c];
+        }
+        col_fft.process(&mut input, &mut column);
+        for r in 0..rows {
+            output[r * cols + c] = column[r];
+        }
+    }
+    output
+}
+
+
+/// Discrete cosine transform (DCT-II) of the real signal.
+/// X[k] = sum x[n] * cos(pi * k * (2n + 1) / 2N)
+/// Computed with a single N point FFT of the reordered signal.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::fft;
+/// 
+/// let coeffs = fft::dct2(&vec![1.0, 1.0, 1.0, 1.0]);
+/// assert_eq!(coeffs[0], 4.0);
+/// assert!(coeffs[1].abs() < 1e-6);
+/// ```
+pub fn dct2(signal: &RealBuffer) -> RealBuffer {
+    let n = signal.len();
+    if n == 0 {
+        return vec![];
+    }
+    // Even samples in order followed by odd samples reversed
+    let mut input: ComplexBuffer = signal.iter().step_by(2)
+        .chain(signal.iter().skip(1).step_by(2).rev())
+        .map(|&v| Complex32::new(v, 0.0))
+        .collect();
+    let mut spectrum = vec![Complex32::new(0.0, 0.0); n];
+    ForwardFFT::new(n).process(&mut input, &mut spectrum);
+    spectrum.iter().enumerate()
+        .map(|(k, v)| (dct_twiddle(k, n).conj() * v).re)
+        .collect()
+}
+
+/// Inverse of dct2 (scaled DCT-III), so idct2(dct2(x)) == x
+pub fn idct2(coeffs: &RealBuffer) -> RealBuffer {
+    let n = coeffs.len();
+    if n == 0 {
+        return vec![];
+    }
+    let mut input: ComplexBuffer = (0..n)
+        .map(|k| {
+            let mirrored = if k == 0 { 0.0 } else { coeffs[n - k] };
+            dct_twiddle(k, n) * Complex32::new(coeffs[k], -mirrored)
+        })
+        .collect();
+    let mut reordered = vec![Complex32::new(0.0, 0.0); n];
+    InverseFFT::new(n).process(&mut input, &mut reordered);
+    (0..n)
+        .map(|i| {
+            let j = if i % 2 == 0 { i / 2 } else { n - 1 - i / 2 };
+            reordered[j].re / n as f32
+        })
+        .collect()
+}
+
+/// Orthonormal DCT-II. Coefficient k is scaled by sqrt(1/N) for k = 0 and sqrt(2/N) otherwise,
+/// which makes the transform matrix orthogonal and preserves energy.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::fft;
+/// 
+/// let coeffs = fft::dct2_ortho(&vec![1.0, 1.0, 1.0, 1.0]);
+/// assert_eq!(coeffs[0], 2.0);
+/// ```
+pub fn dct2_ortho(signal: &RealBuffer) -> RealBuffer {
+    let n = signal.len();
+    dct2(signal).iter().enumerate()
+        .map(|(k, v)| v * dct_ortho_scale(k, n))
+        .collect()
+}
+
+/// Inverse of dct2_ortho, so idct2_ortho(dct2_ortho(x)) == x
+pub fn idct2_ortho(coeffs: &RealBuffer) -> RealBuffer {
+    let n = coeffs.len();
+    let unscaled: RealBuffer = coeffs.iter().enumerate()
+        .map(|(k, v)| v / dct_ortho_scale(k, n))
+        .collect();
+    idct2(&unscaled)
+}
+
+/// exp(i*pi*k / 2N)
+fn dct_twiddle(k: usize, n: usize) -> Complex32 {
+    let angle = std::f64::consts::PI * k as f64 / (2 * n) as f64;
+    Complex32::new(angle.cos() as f32, angle.sin() as f32)
+}
+
+fn dct_ortho_scale(k: usize, n: usize) -> f32 {
+    if k == 0 { (1.0 / n as f32).sqrt() } else { (2.0 / n as f32).sqrt() }
+}
+
+
+/// Shift the spectrum so zero frequency is in the center.
+/// Negative frequencies are moved before positive ones.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::fft;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let spectrum: Vec<Complex32> = (0..5).map(|i| Complex32::new(i as f32, 0.0)).collect();
+/// let shifted: Vec<f32> = fft::fftshift(&spectrum).iter().map(|c| c.re).collect();
+/// assert_eq!(shifted, vec![3.0, 4.0, 0.0, 1.0, 2.0]);
+/// ```
+pub fn fftshift(spectrum: &ComplexBuffer) -> ComplexBuffer {
+    let mut output = spectrum.clone();
+    output.rotate_right(spectrum.len() / 2);
+    output
+}
+
+/// Inverse of fftshift. Moves zero frequency back to index 0.
+pub fn ifftshift(spectrum: &ComplexBuffer) -> ComplexBuffer {
+    let mut output = spectrum.clone();
+    output.rotate_left(spectrum.len() / 2);
+    output
+}
+
+
 /// ------------------------------------------------------------------------------------------------
 /// Module unit tests
 /// ------------------------------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
+    use assert_approx_eq::assert_approx_eq;
     use super::*;
     use crate::num_complex::Complex;
+    use crate::SourceNode;
+    use crate::generators::{GenNode, WhiteNoiseGen};
+
+    #[test]
+    fn test_fft_f64() {
+        let mut input: Vec<Complex64> = (0..8).map(|i| Complex64::new(i as f64, 0.0)).collect();
+        let original = input.clone();
+        let mut spectrum = vec![Complex64::new(0.0, 0.0); 8];
+        let mut output = vec![Complex64::new(0.0, 0.0); 8];
+        ForwardFFT::new(8).process(&mut input, &mut spectrum);
+        assert_eq!(spectrum[0], Complex64::new(28.0, 0.0));
+        InverseFFT::new(8).process(&mut spectrum, &mut output);
+        for (x, y) in output.iter().zip(&original) {
+            assert!((x / 8.0 - y).norm() < 1e-12);
+        }
+    }
 
     #[test]
     fn test_fft() {
@@ -141,4 +526,168 @@ mod tests {
                 Complex::new(1., 0.)];
         assert_eq!(&output, &expected);
     }
+
+    #[test]
+    fn test_ifft_round_trip() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(5)), 64.0, 64);
+        let mut fft = ForwardFFTNode::new(64);
+        let mut ifft = ComplexInverseFFTNode::new(64);
+        let signal = gen.next_frame();
+        let output = ifft.process(fft.process(signal));
+        for i in 0..64 {
+            assert_approx_eq!(output[i].re, signal[i], 1e-5f32);
+            assert_approx_eq!(output[i].im, 0.0, 1e-5f32);
+        }
+    }
+
+    #[test]
+    fn test_rfft() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(5)), 64.0, 64);
+        let mut fft = ForwardFFTNode::new(64);
+        let signal = gen.next_frame();
+        let full = fft.process(signal);
+        let half = rfft(signal);
+        assert_eq!(half.len(), 33);
+        for i in 0..33 {
+            assert_approx_eq!(half[i].re, full[i].re, 1e-5f32);
+            assert_approx_eq!(half[i].im, full[i].im, 1e-5f32);
+        }
+    }
+
+    #[test]
+    fn test_irfft() {
+        for &size in [16, 15].iter() {
+            let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(5)), 64.0, size);
+            let signal = gen.next_frame();
+            let output = irfft(&rfft(signal), size);
+            for i in 0..size {
+                assert_approx_eq!(output[i], signal[i], 1e-5f32);
+            }
+        }
+    }
+
+    /// Reference O(N^2) DFT
+    fn naive_dft(input: &ComplexBuffer) -> ComplexBuffer {
+        let n = input.len();
+        (0..n).map(|k| {
+            input.iter().enumerate().map(|(i, x)| {
+                let w = -2.0 * std::f32::consts::PI * (i * k) as f32 / n as f32;
+                x * Complex::new(w.cos(), w.sin())
+            }).sum()
+        }).collect()
+    }
+
+    #[test]
+    fn test_fft_any_size() {
+        for &size in [5, 12, 1000].iter() {
+            let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(5)), 64.0, size);
+            let mut input: ComplexBuffer = gen.next_frame().iter().map(|&v| Complex::new(v, 0.0)).collect();
+            let expected = naive_dft(&input);
+            let mut output = vec![Complex::new(0., 0.); size];
+            ForwardFFT::new(size).process(&mut input, &mut output);
+            for i in 0..size {
+                assert_approx_eq!(output[i].re, expected[i].re, 1e-2f32);
+                assert_approx_eq!(output[i].im, expected[i].im, 1e-2f32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_complex_fft_node() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(5)), 64.0, 64);
+        let mut node = ComplexForwardFFTNode::new(64);
+        let mut ft = ForwardFFT::new(64);
+        for _ in 0..3 {
+            let signal: ComplexBuffer = gen.next_frame().iter().map(|&v| Complex::new(v, 0.0)).collect();
+            let mut input = signal.clone();
+            let mut expected = vec![Complex::new(0., 0.); 64];
+            ft.process(&mut input, &mut expected);
+            assert_eq!(node.process(&signal), &expected);
+        }
+    }
+
+    #[test]
+    fn test_fft_in_place() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(5)), 64.0, 256);
+        let mut buf: ComplexBuffer = gen.next_frame().iter().map(|&v| Complex::new(v, 0.0)).collect();
+        let mut input = buf.clone();
+        let mut expected = vec![Complex::new(0., 0.); 256];
+        ForwardFFT::new(256).process(&mut input, &mut expected);
+        fft_in_place(&mut buf);
+        for i in 0..256 {
+            assert_approx_eq!(buf[i].re, expected[i].re, 1e-4f32);
+            assert_approx_eq!(buf[i].im, expected[i].im, 1e-4f32);
+        }
+    }
+
+    #[test]
+    fn test_fftshift() {
+        let to_complex = |xs: &[f32]| -> ComplexBuffer { xs.iter().map(|&x| Complex::new(x, 0.0)).collect() };
+        let even = to_complex(&[0.0, 1.0, 2.0, 3.0]);
+        let odd = to_complex(&[0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(fftshift(&even), to_complex(&[2.0, 3.0, 0.0, 1.0]));
+        assert_eq!(fftshift(&odd), to_complex(&[3.0, 4.0, 0.0, 1.0, 2.0]));
+        assert_eq!(ifftshift(&fftshift(&even)), even);
+        assert_eq!(ifftshift(&fftshift(&odd)), odd);
+        assert_eq!(ifftshift(&odd), to_complex(&[2.0, 3.0, 4.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_fft2() {
+        let mut data = vec![Complex::new(0., 0.); 16];
+        data[5] = Complex::new(1., 0.);
+        let spectrum = fft2(&data, 4, 4);
+        for c in spectrum.iter() {
+            assert_approx_eq!(c.norm(), 1.0, 1e-5f32);
+        }
+        let output = ifft2(&spectrum, 4, 4);
+        for i in 0..16 {
+            assert_approx_eq!(output[i].re, data[i].re, 1e-5f32);
+            assert_approx_eq!(output[i].im, 0.0, 1e-5f32);
+        }
+
+        // Rectangular matrix with the constant rows
+        let data: ComplexBuffer = (0..6).map(|i| Complex::new((i / 3) as f32, 0.0)).collect();
+        let spectrum = fft2(&data, 2, 3);
+        let expected = [3.0, 0.0, 0.0, -3.0, 0.0, 0.0];
+        for i in 0..6 {
+            assert_approx_eq!(spectrum[i].re, expected[i], 1e-5f32);
+        }
+    }
+
+    fn naive_dct2(signal: &RealBuffer) -> RealBuffer {
+        let n = signal.len();
+        (0..n)
+            .map(|k| signal.iter().enumerate()
+                .map(|(i, x)| x * (std::f32::consts::PI * k as f32 * (2 * i + 1) as f32 / (2 * n) as f32).cos())
+                .sum())
+            .collect()
+    }
+
+    #[test]
+    fn test_dct2_naive() {
+        for &size in &[1, 5, 8] {
+            let signal: RealBuffer = (0..size).map(|i| ((i * 7) % 5) as f32 - 1.5).collect();
+            let coeffs = dct2(&signal);
+            for (x, y) in coeffs.iter().zip(&naive_dct2(&signal)) {
+                assert_approx_eq!(x, y, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dct2_round_trip() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(5)), 64.0, 37);
+        let signal = gen.next_frame().clone();
+        for (x, y) in idct2(&dct2(&signal)).iter().zip(&signal) {
+            assert_approx_eq!(x, y, 1e-4);
+        }
+        let coeffs = dct2_ortho(&signal);
+        let energy: f32 = signal.iter().map(|v| v * v).sum();
+        let coeffs_energy: f32 = coeffs.iter().map(|v| v * v).sum();
+        assert_approx_eq!(energy, coeffs_energy, 1e-3);
+        for (x, y) in idct2_ortho(&coeffs).iter().zip(&signal) {
+            assert_approx_eq!(x, y, 1e-4);
+        }
+    }
 }
@@ -0,0 +1,1124 @@
+//! Digital filters
+//!
+//! Filter nodes keep their internal state between frames,
+//! so the signal can be processed in consecutive frames.
+//!
+
+use std::f32::consts::PI;
+use crate::num_complex::Complex64;
+use crate::{RealBuffer, ProcessingNode};
+use crate::windows;
+
+
+/// Finite impulse response filter
+/// y[n] = sum(taps[k] * x[n-k])
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::filters::FirNode;
+/// 
+/// let mut fir = FirNode::new(vec![0.5, 0.5], 4);
+/// assert_eq!(fir.process(&vec![1.0, 1.0, 1.0, 1.0]), &vec![0.5, 1.0, 1.0, 1.0]);
+/// assert_eq!(fir.process(&vec![0.0, 0.0, 0.0, 0.0]), &vec![0.5, 0.0, 0.0, 0.0]);
+/// ```
+pub struct FirNode {
+    taps: RealBuffer,
+    // Last taps.len()-1 input samples. The most recent is at the end.
+    history: RealBuffer,
+    output: RealBuffer,
+}
+
+impl FirNode {
+    /// Create FIR filter
+    ///   * taps - Filter coefficients (impulse response)
+    ///   * frame_size - Size of the output buffer
+    pub fn new(taps: RealBuffer, frame_size: usize) -> FirNode {
+        let history = vec![0.0; taps.len().saturating_sub(1)];
+        FirNode { taps, history, output: vec![0.0; frame_size] }
+    }
+}
+
+impl ProcessingNode for FirNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        let n = usize::min(input.len(), self.output.len());
+        let h = self.history.len();
+        for i in 0..n {
+            let mut acc = 0.0;
+            for (k, tap) in self.taps.iter().enumerate() {
+                // Samples before the current frame are taken from the history
+                acc += tap * if k <= i { input[i - k] } else { self.history[h + i - k] };
+            }
+            self.output[i] = acc;
+        }
+        // Keep the last samples for the next frame
+        if n >= h {
+            self.history.copy_from_slice(&input[n - h..n]);
+        } else {
+            self.history.rotate_left(n);
+            self.history[h - n..].copy_from_slice(&input[..n]);
+        }
+        &self.output
+    }
+}
+
+/// Design lowpass FIR filter with windowed sinc method (Blackman window).
+/// The taps are normalized to unity gain at DC.
+///   * num_taps - Filter length. Odd lengths give integer group delay (num_taps-1)/2
+///   * cutoff_hz - Cutoff frequency (-6 dB point)
+///   * sample_rate - Sampling frequency
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::filters::fir_lowpass;
+/// 
+/// let taps = fir_lowpass(31, 1000.0, 8000.0);
+/// assert_eq!(taps.len(), 31);
+/// assert_approx_eq!(taps.iter().sum::<f32>(), 1.0, 1e-6);
+/// ```
+pub fn fir_lowpass(num_taps: usize, cutoff_hz: f32, sample_rate: f32) -> RealBuffer {
+    let fc = cutoff_hz / sample_rate;
+    let center = (num_taps as f32 - 1.0) / 2.0;
+    let window = windows::blackman(num_taps, 0, num_taps);
+    let mut taps: RealBuffer = window.samples().iter()
+        .enumerate()
+        .map(|(i, w)| {
+            let x = i as f32 - center;
+            let sinc = if x == 0.0 { 2.0 * fc } else { (2.0 * PI * fc * x).sin() / (PI * x) };
+            sinc * w
+        })
+        .collect();
+    let sum: f32 = taps.iter().sum();
+    taps.iter_mut().for_each(|t| *t /= sum);
+    taps
+}
+
+
+/// Moving average (boxcar) filter.
+/// Until the window is filled, the average is computed over the samples seen so far.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::filters::MovingAverageNode;
+/// 
+/// let mut filter = MovingAverageNode::new(2, 4);
+/// assert_eq!(filter.process(&vec![2.0, 4.0, 6.0, 8.0]), &vec![2.0, 3.0, 5.0, 7.0]);
+/// ```
+pub struct MovingAverageNode {
+    // Circular buffer with the last window samples
+    history: Vec<f64>,
+    pos: usize,
+    count: usize,
+    sum: f64,
+    output: RealBuffer,
+}
+
+impl MovingAverageNode {
+    /// Create moving average filter
+    ///   * window - Number of averaged samples. Must be greater than 0
+    ///   * frame_size - Size of the output buffer
+    pub fn new(window: usize, frame_size: usize) -> MovingAverageNode {
+        assert!(window > 0, "Window size must be greater than 0");
+        MovingAverageNode {
+            history: vec![0.0; window],
+            pos: 0,
+            count: 0,
+            sum: 0.0,
+            output: vec![0.0; frame_size],
+        }
+    }
+}
+
+impl ProcessingNode for MovingAverageNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        let window = self.history.len();
+        for (y, &x) in self.output.iter_mut().zip(input) {
+            self.sum += x as f64 - self.history[self.pos];
+            self.history[self.pos] = x as f64;
+            self.pos = (self.pos + 1) % window;
+            self.count = usize::min(self.count + 1, window);
+            *y = (self.sum / self.count as f64) as f32;
+        }
+        &self.output
+    }
+}
+
+/// Median filter for removing impulse noise.
+/// Output is the median of the last window input samples, so the signal is delayed by (window-1)/2.
+/// Until the window is filled, the median is computed over the samples seen so far
+/// (the mean of the two middle values for even count).
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::filters::MedianNode;
+/// 
+/// let mut filter = MedianNode::new(3, 5);
+/// assert_eq!(filter.process(&vec![1.0, 1.0, 9.0, 1.0, 1.0]), &vec![1.0, 1.0, 1.0, 1.0, 1.0]);
+/// ```
+pub struct MedianNode {
+    // Circular buffer with the last window samples
+    history: RealBuffer,
+    pos: usize,
+    count: usize,
+    sorted: RealBuffer,
+    output: RealBuffer,
+}
+
+impl MedianNode {
+    /// Create median filter
+    ///   * window - Number of samples. Must be odd
+    ///   * frame_size - Size of the output buffer
+    pub fn new(window: usize, frame_size: usize) -> MedianNode {
+        assert!(window % 2 == 1, "Window size must be odd");
+        MedianNode {
+            history: vec![0.0; window],
+            pos: 0,
+            count: 0,
+            sorted: Vec::with_capacity(window),
+            output: vec![0.0; frame_size],
+        }
+    }
+}
+
+impl ProcessingNode for MedianNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        let window = self.history.len();
+        for (y, &x) in self.output.iter_mut().zip(input) {
+            self.history[self.pos] = x;
+            self.pos = (self.pos + 1) % window;
+            self.count = usize::min(self.count + 1, window);
+            self.sorted.clear();
+            // Until the window is filled, the samples are at its beginning
+            self.sorted.extend_from_slice(&self.history[..self.count]);
+            self.sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let mid = self.count / 2;
+            *y = if self.count % 2 == 1 { self.sorted[mid] } else { (self.sorted[mid - 1] + self.sorted[mid]) / 2.0 };
+        }
+        &self.output
+    }
+}
+
+
+/// DC blocking filter
+/// y[n] = x[n] - x[n-1] + r*y[n-1]
+/// 
+/// The cutoff frequency is approximately (1 - r) * sample_rate / (2π).
+/// For r = 0.995 at 44100 Hz it is around 35 Hz. Values closer to 1 give lower cutoff.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::filters::DcBlockNode;
+/// 
+/// let mut filter = DcBlockNode::new(0.5, 4);
+/// assert_eq!(filter.process(&vec![1.0, 1.0, 1.0, 1.0]), &vec![1.0, 0.5, 0.25, 0.125]);
+/// ```
+pub struct DcBlockNode {
+    r: f32,
+    last_input: f32,
+    last_output: f32,
+    output: RealBuffer,
+}
+
+impl DcBlockNode {
+    /// Create DC blocking filter
+    ///   * r - Pole position. Should be in range (0, 1), typically 0.99 - 0.999
+    ///   * frame_size - Size of the output buffer
+    pub fn new(r: f32, frame_size: usize) -> DcBlockNode {
+        DcBlockNode { r, last_input: 0.0, last_output: 0.0, output: vec![0.0; frame_size] }
+    }
+}
+
+impl ProcessingNode for DcBlockNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, &x) in self.output.iter_mut().zip(input) {
+            *y = x - self.last_input + self.r * self.last_output;
+            self.last_input = x;
+            self.last_output = *y;
+        }
+        &self.output
+    }
+}
+
+
+/// Feedback comb filter
+/// y[n] = x[n] + feedback*y[n-delay]
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::filters::CombNode;
+/// 
+/// let mut filter = CombNode::new(2, 0.5, 6);
+/// let output = filter.process(&vec![1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+/// assert_eq!(output, &vec![1.0, 0.0, 0.5, 0.0, 0.25, 0.0]);
+/// ```
+pub struct CombNode {
+    feedback: f32,
+    // Circular buffer with the last delay output samples
+    history: RealBuffer,
+    pos: usize,
+    output: RealBuffer,
+}
+
+impl CombNode {
+    /// Create comb filter
+    ///   * delay_samples - Echo delay. Must be greater than 0
+    ///   * feedback - Echo gain. |feedback| must be less than 1, otherwise filter is unstable
+    ///   * frame_size - Size of the output buffer
+    pub fn new(delay_samples: usize, feedback: f32, frame_size: usize) -> CombNode {
+        assert!(delay_samples > 0, "Delay must be greater than 0");
+        assert!(feedback.abs() < 1.0, "Feedback must be in range (-1, 1)");
+        CombNode { feedback, history: vec![0.0; delay_samples], pos: 0, output: vec![0.0; frame_size] }
+    }
+}
+
+impl ProcessingNode for CombNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        let delay = self.history.len();
+        for (y, &x) in self.output.iter_mut().zip(input) {
+            *y = x + self.feedback * self.history[self.pos];
+            self.history[self.pos] = *y;
+            self.pos = (self.pos + 1) % delay;
+        }
+        &self.output
+    }
+}
+
+
+/// Biquad IIR filter implemented in Direct Form II transposed.
+/// Coefficients are normalized by a[0].
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::filters::{BiquadNode, biquad_lowpass};
+/// 
+/// let (b, a) = biquad_lowpass(1000.0, 0.707, 44100.0);
+/// let mut filter = BiquadNode::new(b, a, 4);
+/// let output = filter.process(&vec![1.0, 0.0, 0.0, 0.0]);
+/// assert!(output[0] > 0.0 && output[0] < 0.01);
+/// ```
+pub struct BiquadNode {
+    b: [f32; 3],
+    a: [f32; 3],
+    z1: f32,
+    z2: f32,
+    output: RealBuffer,
+}
+
+impl BiquadNode {
+    /// Create biquad filter
+    ///   * b - Numerator (feedforward) coefficients
+    ///   * a - Denominator (feedback) coefficients
+    ///   * frame_size - Size of the output buffer
+    pub fn new(b: [f32; 3], a: [f32; 3], frame_size: usize) -> BiquadNode {
+        let a0 = a[0];
+        BiquadNode {
+            b: [b[0] / a0, b[1] / a0, b[2] / a0],
+            a: [1.0, a[1] / a0, a[2] / a0],
+            z1: 0.0,
+            z2: 0.0,
+            output: vec![0.0; frame_size],
+        }
+    }
+}
+
+impl ProcessingNode for BiquadNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, &x) in self.output.iter_mut().zip(input) {
+            *y = self.b[0] * x + self.z1;
+            self.z1 = self.b[1] * x - self.a[1] * *y + self.z2;
+            self.z2 = self.b[2] * x - self.a[2] * *y;
+        }
+        &self.output
+    }
+}
+
+/// Biquad lowpass filter coefficients (b, a)
+/// https://www.w3.org/TR/audio-eq-cookbook/
+pub fn biquad_lowpass(cutoff_hz: f32, q: f32, sample_rate: f32) -> ([f32; 3], [f32; 3]) {
+    let (cos_w, alpha) = biquad_params(cutoff_hz, q, sample_rate);
+    let b1 = 1.0 - cos_w;
+    ([b1 / 2.0, b1, b1 / 2.0], [1.0 + alpha, -2.0 * cos_w, 1.0 - alpha])
+}
+
+/// Biquad highpass filter coefficients (b, a)
+pub fn biquad_highpass(cutoff_hz: f32, q: f32, sample_rate: f32) -> ([f32; 3], [f32; 3]) {
+    let (cos_w, alpha) = biquad_params(cutoff_hz, q, sample_rate);
+    let b1 = 1.0 + cos_w;
+    ([b1 / 2.0, -b1, b1 / 2.0], [1.0 + alpha, -2.0 * cos_w, 1.0 - alpha])
+}
+
+/// Biquad bandpass filter coefficients (b, a) with 0 dB peak gain
+pub fn biquad_bandpass(center_hz: f32, q: f32, sample_rate: f32) -> ([f32; 3], [f32; 3]) {
+    let (cos_w, alpha) = biquad_params(center_hz, q, sample_rate);
+    ([alpha, 0.0, -alpha], [1.0 + alpha, -2.0 * cos_w, 1.0 - alpha])
+}
+
+/// Biquad notch filter coefficients (b, a)
+pub fn biquad_notch(center_hz: f32, q: f32, sample_rate: f32) -> ([f32; 3], [f32; 3]) {
+    let (cos_w, alpha) = biquad_params(center_hz, q, sample_rate);
+    ([1.0, -2.0 * cos_w, 1.0], [1.0 + alpha, -2.0 * cos_w, 1.0 - alpha])
+}
+
+/// Peaking EQ coefficients (b, a). Gain at center_hz is gain_db, far from it the gain is 0 dB.
+pub fn peaking_eq(center_hz: f32, q: f32, gain_db: f32, sample_rate: f32) -> ([f32; 3], [f32; 3]) {
+    let (cos_w, alpha) = biquad_params(center_hz, q, sample_rate);
+    let amp = 10f32.powf(gain_db / 40.0);
+    ([1.0 + alpha * amp, -2.0 * cos_w, 1.0 - alpha * amp],
+     [1.0 + alpha / amp, -2.0 * cos_w, 1.0 - alpha / amp])
+}
+
+/// Low shelf coefficients (b, a). Frequencies below freq_hz get gain_db, high frequencies 0 dB.
+/// Q controls the slope of the transition, 0.707 gives the steepest one without overshoot.
+pub fn low_shelf(freq_hz: f32, q: f32, gain_db: f32, sample_rate: f32) -> ([f32; 3], [f32; 3]) {
+    let (cos_w, alpha) = biquad_params(freq_hz, q, sample_rate);
+    let amp = 10f32.powf(gain_db / 40.0);
+    let beta = 2.0 * amp.sqrt() * alpha;
+    ([amp * ((amp + 1.0) - (amp - 1.0) * cos_w + beta),
+      2.0 * amp * ((amp - 1.0) - (amp + 1.0) * cos_w),
+      amp * ((amp + 1.0) - (amp - 1.0) * cos_w - beta)],
+     [(amp + 1.0) + (amp - 1.0) * cos_w + beta,
+      -2.0 * ((amp - 1.0) + (amp + 1.0) * cos_w),
+      (amp + 1.0) + (amp - 1.0) * cos_w - beta])
+}
+
+/// High shelf coefficients (b, a). Frequencies above freq_hz get gain_db, low frequencies 0 dB.
+/// See low_shelf for the meaning of Q.
+pub fn high_shelf(freq_hz: f32, q: f32, gain_db: f32, sample_rate: f32) -> ([f32; 3], [f32; 3]) {
+    let (cos_w, alpha) = biquad_params(freq_hz, q, sample_rate);
+    let amp = 10f32.powf(gain_db / 40.0);
+    let beta = 2.0 * amp.sqrt() * alpha;
+    ([amp * ((amp + 1.0) + (amp - 1.0) * cos_w + beta),
+      -2.0 * amp * ((amp - 1.0) + (amp + 1.0) * cos_w),
+      amp * ((amp + 1.0) + (amp - 1.0) * cos_w - beta)],
+     [(amp + 1.0) - (amp - 1.0) * cos_w + beta,
+      2.0 * ((amp - 1.0) - (amp + 1.0) * cos_w),
+      (amp + 1.0) - (amp - 1.0) * cos_w - beta])
+}
+
+// Returns cos(w0) and alpha used by the cookbook formulas
+fn biquad_params(freq: f32, q: f32, sample_rate: f32) -> (f32, f32) {
+    let w0 = 2.0 * PI * freq / sample_rate;
+    (w0.cos(), w0.sin() / (2.0 * q))
+}
+
+/// Butterworth lowpass filter designed as a cascade of second order sections.
+/// Each section is [b0, b1, b2, a0, a1, a2]. Odd orders end with a first order section.
+///   * order - Filter order
+///   * cutoff_hz - Frequency of the -3 dB point
+///   * sample_rate - Sampling frequency
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::filters::{BiquadNode, butterworth_lowpass};
+/// 
+/// let sections = butterworth_lowpass(4, 1000.0, 8000.0);
+/// assert_eq!(sections.len(), 2);
+/// let mut filters: Vec<BiquadNode> = sections.iter()
+///     .map(|s| BiquadNode::new([s[0], s[1], s[2]], [s[3], s[4], s[5]], 256))
+///     .collect();
+/// let mut signal = vec![1.0; 256];
+/// for filter in filters.iter_mut() {
+///     signal = filter.process(&signal).clone();
+/// }
+/// assert!((signal[255] - 1.0).abs() < 1e-4);
+/// ```
+pub fn butterworth_lowpass(order: usize, cutoff_hz: f32, sample_rate: f32) -> Vec<[f32; 6]> {
+    butterworth(order, cutoff_hz, sample_rate, false)
+}
+
+/// Butterworth highpass filter designed as a cascade of second order sections.
+/// See butterworth_lowpass for the section layout.
+pub fn butterworth_highpass(order: usize, cutoff_hz: f32, sample_rate: f32) -> Vec<[f32; 6]> {
+    butterworth(order, cutoff_hz, sample_rate, true)
+}
+
+/// Butterworth bandpass filter designed as a cascade of second order sections.
+/// Lowpass prototype of the given order is transformed to bandpass (s -> (s^2 + w0^2) / (s*B)),
+/// which doubles the order, so there is one section per pole pair (order sections in total).
+/// Each section has zeros at DC and Nyquist and is normalized to unity gain at the center frequency.
+/// See butterworth_lowpass for the section layout.
+///   * low_hz - Lower -3 dB frequency
+///   * high_hz - Upper -3 dB frequency
+pub fn butterworth_bandpass(order: usize, low_hz: f32, high_hz: f32, sample_rate: f32) -> Vec<[f32; 6]> {
+    assert!(low_hz > 0.0 && low_hz < high_hz && high_hz < sample_rate / 2.0,
+            "Band edges must satisfy 0 < low_hz < high_hz < sample_rate / 2");
+    let fs2 = 2.0 * sample_rate as f64;
+    // Prewarped analog band edges for the bilinear transform
+    let prewarp = |f: f32| fs2 * (std::f64::consts::PI * f as f64 / sample_rate as f64).tan();
+    let (low, high) = (prewarp(low_hz), prewarp(high_hz));
+    let w0_sq = low * high;
+    let bandwidth = high - low;
+
+    let mut complex_poles = vec![];
+    let mut real_poles = vec![];
+    for k in 0..order {
+        let theta = std::f64::consts::PI * (2 * k + 1) as f64 / (2 * order) as f64;
+        let p = Complex64::new(-theta.sin(), theta.cos());
+        // Roots of s^2 - p*B*s + w0^2 = 0
+        let pb = p * bandwidth;
+        let d = (pb * pb - 4.0 * w0_sq).sqrt();
+        for s in [(pb + d) / 2.0, (pb - d) / 2.0].iter() {
+            if s.im.abs() < 1e-9 * w0_sq.sqrt() {
+                real_poles.push(s.re);
+            } else if s.im > 0.0 {
+                complex_poles.push(*s);
+            }
+        }
+    }
+    let bilinear = |s: Complex64| (fs2 + s) / (fs2 - s);
+    // Denominators as [1, a1, a2]
+    let mut denominators: Vec<[f64; 2]> = complex_poles.iter()
+        .map(|&s| {
+            let z = bilinear(s);
+            [-2.0 * z.re, z.norm_sqr()]
+        })
+        .collect();
+    for pair in real_poles.chunks(2) {
+        let z1 = bilinear(Complex64::new(pair[0], 0.0)).re;
+        let z2 = bilinear(Complex64::new(pair[1], 0.0)).re;
+        denominators.push([-(z1 + z2), z1 * z2]);
+    }
+
+    let w_center = 2.0 * (w0_sq.sqrt() / fs2).atan();
+    let z_center = Complex64::new(w_center.cos(), w_center.sin());
+    denominators.iter()
+        .map(|a| {
+            let z_inv = z_center.inv();
+            let response = (1.0 - z_inv * z_inv) / (1.0 + a[0] * z_inv + a[1] * z_inv * z_inv);
+            let g = (1.0 / response.norm()) as f32;
+            [g, 0.0, -g, 1.0, a[0] as f32, a[1] as f32]
+        })
+        .collect()
+}
+
+fn butterworth(order: usize, cutoff_hz: f32, sample_rate: f32, highpass: bool) -> Vec<[f32; 6]> {
+    let design = if highpass { biquad_highpass } else { biquad_lowpass };
+    // Pole pairs of the analog prototype give the Q of each section
+    let mut sections: Vec<[f32; 6]> = (0..order / 2)
+        .map(|k| {
+            let theta = PI * (2 * k + 1) as f32 / (2 * order) as f32;
+            let (b, a) = design(cutoff_hz, 1.0 / (2.0 * theta.sin()), sample_rate);
+            [b[0], b[1], b[2], a[0], a[1], a[2]]
+        })
+        .collect();
+    if order % 2 == 1 {
+        // Real pole, bilinear transform with prewarping
+        let k = (PI * cutoff_hz / sample_rate).tan();
+        let a1 = (k - 1.0) / (k + 1.0);
+        let b0 = if highpass { 1.0 / (k + 1.0) } else { k / (k + 1.0) };
+        let b1 = if highpass { -b0 } else { b0 };
+        sections.push([b0, b1, 0.0, 1.0, a1, 0.0]);
+    }
+    sections
+}
+
+
+/// Output of the state variable filter returned by process
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SvfMode {
+    Lowpass,
+    Highpass,
+    Bandpass,
+    Notch,
+}
+
+/// State variable filter (topology preserving transform version).
+/// All four outputs are computed for each frame. Process returns the one selected by mode,
+/// the other ones are available from the accessor methods until the next frame is processed.
+/// Bandpass output has the gain of q at the cutoff frequency, so lowpass + highpass + bandpass / q
+/// reconstructs the input.
+/// https://cytomic.com/files/dsp/SvfLinearTrapOptimised2.pdf
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::filters::SvfNode;
+/// 
+/// let mut filter = SvfNode::new(1000.0, 0.707, 44100.0, 256);
+/// let output = filter.process(&vec![1.0; 256]).clone();
+/// assert!((output[255] - 1.0).abs() < 1e-3);
+/// assert!(filter.highpass()[255].abs() < 1e-3);
+/// ```
+pub struct SvfNode {
+    mode: SvfMode,
+    k: f32,
+    a1: f32,
+    a2: f32,
+    a3: f32,
+    ic1: f32,
+    ic2: f32,
+    lowpass: RealBuffer,
+    highpass: RealBuffer,
+    bandpass: RealBuffer,
+    notch: RealBuffer,
+}
+
+impl SvfNode {
+    /// Create state variable filter with the lowpass output
+    ///   * cutoff_hz - Cutoff (center) frequency
+    ///   * q - Resonance. 0.707 gives Butterworth response
+    ///   * sample_rate - Sampling frequency
+    ///   * frame_size - Size of the output buffers
+    pub fn new(cutoff_hz: f32, q: f32, sample_rate: f32, frame_size: usize) -> SvfNode {
+        SvfNode::with_mode(cutoff_hz, q, sample_rate, SvfMode::Lowpass, frame_size)
+    }
+
+    /// Create state variable filter which returns the output selected by mode
+    pub fn with_mode(cutoff_hz: f32, q: f32, sample_rate: f32, mode: SvfMode, frame_size: usize) -> SvfNode {
+        let g = (PI * cutoff_hz / sample_rate).tan();
+        let k = 1.0 / q;
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        SvfNode {
+            mode,
+            k,
+            a1,
+            a2: g * a1,
+            a3: g * g * a1,
+            ic1: 0.0,
+            ic2: 0.0,
+            lowpass: vec![0.0; frame_size],
+            highpass: vec![0.0; frame_size],
+            bandpass: vec![0.0; frame_size],
+            notch: vec![0.0; frame_size],
+        }
+    }
+
+    /// Change which output is returned by process
+    pub fn set_mode(&mut self, mode: SvfMode) {
+        self.mode = mode;
+    }
+
+    /// Lowpass output of the last processed frame
+    pub fn lowpass(&self) -> &RealBuffer {
+        &self.lowpass
+    }
+
+    /// Highpass output of the last processed frame
+    pub fn highpass(&self) -> &RealBuffer {
+        &self.highpass
+    }
+
+    /// Bandpass output of the last processed frame
+    pub fn bandpass(&self) -> &RealBuffer {
+        &self.bandpass
+    }
+
+    /// Notch output of the last processed frame
+    pub fn notch(&self) -> &RealBuffer {
+        &self.notch
+    }
+}
+
+impl ProcessingNode for SvfNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        let n = usize::min(input.len(), self.lowpass.len());
+        for (i, &x) in input.iter().enumerate().take(n) {
+            let v3 = x - self.ic2;
+            let v1 = self.a1 * self.ic1 + self.a2 * v3;
+            let v2 = self.ic2 + self.a2 * self.ic1 + self.a3 * v3;
+            self.ic1 = 2.0 * v1 - self.ic1;
+            self.ic2 = 2.0 * v2 - self.ic2;
+            self.lowpass[i] = v2;
+            self.bandpass[i] = v1;
+            self.highpass[i] = x - self.k * v1 - v2;
+            self.notch[i] = x - self.k * v1;
+        }
+        match self.mode {
+            SvfMode::Lowpass => &self.lowpass,
+            SvfMode::Highpass => &self.highpass,
+            SvfMode::Bandpass => &self.bandpass,
+            SvfMode::Notch => &self.notch,
+        }
+    }
+}
+
+
+/// One pole lowpass filter for smoothing control signals
+/// y[n] = y[n-1] + a*(x[n] - y[n-1])
+/// 
+/// Coefficient a = 1 - exp(-2π * cutoff_hz / sample_rate), so the step response
+/// reaches 63% of the final value after the time constant 1 / (2π * cutoff_hz).
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::filters::OnePoleNode;
+/// 
+/// let mut filter = OnePoleNode::new(100.0, 44100.0, 4);
+/// let output = filter.process(&vec![1.0; 4]);
+/// assert!(output[0] > 0.0 && output[3] > output[0] && output[3] < 1.0);
+/// ```
+pub struct OnePoleNode {
+    a: f32,
+    last_output: f32,
+    output: RealBuffer,
+}
+
+impl OnePoleNode {
+    /// Create one pole lowpass filter
+    ///   * cutoff_hz - Cutoff frequency
+    ///   * sample_rate - Sampling frequency
+    ///   * frame_size - Size of the output buffer
+    pub fn new(cutoff_hz: f32, sample_rate: f32, frame_size: usize) -> OnePoleNode {
+        let a = 1.0 - (-2.0 * PI * cutoff_hz / sample_rate).exp();
+        OnePoleNode { a, last_output: 0.0, output: vec![0.0; frame_size] }
+    }
+}
+
+impl ProcessingNode for OnePoleNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, &x) in self.output.iter_mut().zip(input) {
+            self.last_output += self.a * (x - self.last_output);
+            *y = self.last_output;
+        }
+        &self.output
+    }
+}
+
+
+/// Savitzky-Golay smoothing filter.
+/// Polynomial of poly_order is fitted (least squares) to each window of samples and evaluated
+/// at its center. Since the fit is linear, it is done by convolution with precomputed coefficients.
+/// At the edges the polynomial fitted to the first (last) window is evaluated at each sample,
+/// so the output has the same length as the signal. Polynomials up to poly_order pass unchanged.
+/// https://en.wikipedia.org/wiki/Savitzky%E2%80%93Golay_filter
+///   * window - Odd number of samples in the window. Must not be longer than the signal
+///   * poly_order - Order of the fitted polynomial. Must be less than window
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::filters::savgol;
+/// 
+/// let signal: Vec<f32> = (0..9).map(|i| (i * i) as f32).collect();
+/// let smoothed = savgol(&signal, 5, 2);
+/// for (x, y) in smoothed.iter().zip(&signal) {
+///     assert_approx_eq!(x, y, 1e-3);
+/// }
+/// ```
+pub fn savgol(signal: &RealBuffer, window: usize, poly_order: usize) -> RealBuffer {
+    assert!(window % 2 == 1, "Window size must be odd");
+    assert!(poly_order < window, "Polynomial order must be less than window size");
+    assert!(window <= signal.len(), "Window must not be longer than the signal");
+    let half = window / 2;
+    let n = signal.len();
+    let apply = |coeffs: &[f64], start: usize| -> f32 {
+        coeffs.iter().zip(&signal[start..start + window]).map(|(c, &x)| c * x as f64).sum::<f64>() as f32
+    };
+    let center = savgol_coeffs(window, poly_order, 0.0);
+    let mut output = vec![0.0; n];
+    for (i, y) in output.iter_mut().enumerate().take(n - half).skip(half) {
+        *y = apply(&center, i - half);
+    }
+    for k in 0..half {
+        let offset = (half - k) as f64;
+        output[k] = apply(&savgol_coeffs(window, poly_order, -offset), 0);
+        output[n - 1 - k] = apply(&savgol_coeffs(window, poly_order, offset), n - window);
+    }
+    output
+}
+
+// Weights which evaluate the least squares polynomial at position t relative to the window center.
+// Solves (J'J) a = [1, t, t^2, ...] where J[m][j] = m^j, then weight[m] = sum(a[j] * m^j)
+fn savgol_coeffs(window: usize, poly_order: usize, t: f64) -> Vec<f64> {
+    let half = (window / 2) as f64;
+    let size = poly_order + 1;
+    let positions: Vec<f64> = (0..window).map(|m| m as f64 - half).collect();
+    // Augmented matrix [J'J | e]
+    let mut matrix: Vec<Vec<f64>> = (0..size)
+        .map(|r| {
+            let mut row: Vec<f64> = (0..size)
+                .map(|c| positions.iter().map(|m| m.powi((r + c) as i32)).sum())
+                .collect();
+            row.push(t.powi(r as i32));
+            row
+        })
+        .collect();
+    // Gaussian elimination with partial pivoting
+    for col in 0..size {
+        let pivot = (col..size)
+            .max_by(|&a, &b| matrix[a][col].abs().partial_cmp(&matrix[b][col].abs()).unwrap())
+            .unwrap();
+        matrix.swap(col, pivot);
+        let pivot_row = matrix[col].clone();
+        for (r, row) in matrix.iter_mut().enumerate() {
+            if r != col {
+                let factor = row[col] / pivot_row[col];
+                for (v, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *v -= factor * p;
+                }
+            }
+        }
+    }
+    let a: Vec<f64> = (0..size).map(|r| matrix[r][size] / matrix[r][r]).collect();
+    positions.iter()
+        .map(|m| a.iter().enumerate().map(|(j, aj)| aj * m.powi(j as i32)).sum())
+        .collect()
+}
+
+
+/// ------------------------------------------------------------------------------------------------
+/// Module unit tests
+/// ------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use crate::SourceNode;
+    use crate::fft::rfft;
+    use crate::generators::{GenNode, StepGen, SineGen, WhiteNoiseGen};
+    use super::*;
+
+    // Peak amplitude of the filtered sine after the transient has decayed
+    fn filtered_amplitude(filter: &mut dyn ProcessingNode<InBuffer=RealBuffer, OutBuffer=RealBuffer>,
+                          freq: f32, sample_rate: f32) -> f32 {
+        let mut gen = GenNode::new(Box::new(SineGen::new(freq)), sample_rate, 1024);
+        for _ in 0..4 {
+            filter.process(gen.next_frame());
+        }
+        filter.process(gen.next_frame()).iter().fold(0.0, |acc, x| f32::max(acc, x.abs()))
+    }
+
+    // Frequency response magnitude (in dB) of the cascade, computed from its impulse response
+    fn sos_response_db(sections: &[[f32; 6]], size: usize) -> RealBuffer {
+        let mut signal = vec![0.0; size];
+        signal[0] = 1.0;
+        for s in sections {
+            let mut filter = BiquadNode::new([s[0], s[1], s[2]], [s[3], s[4], s[5]], size);
+            signal = filter.process(&signal).clone();
+        }
+        rfft(&signal).iter().map(|c| 20.0 * c.norm().log10()).collect()
+    }
+
+    #[test]
+    fn test_fir_identity() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(1)), 8.0, 8);
+        let mut fir = FirNode::new(vec![1.0], 8);
+        for _ in 0..3 {
+            let signal = gen.next_frame();
+            assert_eq!(fir.process(signal), signal);
+        }
+    }
+
+    #[test]
+    fn test_fir_moving_average() {
+        let mut gen = GenNode::new(Box::new(StepGen::new(0.25)), 8.0, 4);
+        let mut fir = FirNode::new(vec![0.25; 4], 4);
+        let mut output = fir.process(gen.next_frame()).clone();
+        output.extend(fir.process(gen.next_frame()));
+        let expected = [0.0, 0.0, 0.25, 0.5, 0.75, 1.0, 1.0, 1.0];
+        for i in 0..8 {
+            assert_approx_eq!(output[i], expected[i], 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_fir_frame_shorter_than_taps() {
+        let mut fir = FirNode::new(vec![1.0, 2.0, 3.0, 4.0], 2);
+        assert_eq!(fir.process(&vec![1.0, 0.0]), &vec![1.0, 2.0]);
+        assert_eq!(fir.process(&vec![0.0, 1.0]), &vec![3.0, 5.0]);
+        assert_eq!(fir.process(&vec![0.0, 0.0]), &vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_biquad_lowpass() {
+        let (b, a) = biquad_lowpass(200.0, 0.707, 8000.0);
+        let mut filter = BiquadNode::new(b, a, 1024);
+        assert_approx_eq!(filtered_amplitude(&mut filter, 20.0, 8000.0), 1.0, 0.01);
+        let mut filter = BiquadNode::new(b, a, 1024);
+        assert!(filtered_amplitude(&mut filter, 2000.0, 8000.0) < 0.02);
+    }
+
+    #[test]
+    fn test_biquad_highpass() {
+        let (b, a) = biquad_highpass(1000.0, 0.707, 8000.0);
+        let mut filter = BiquadNode::new(b, a, 1024);
+        assert!(filtered_amplitude(&mut filter, 50.0, 8000.0) < 0.01);
+        let mut filter = BiquadNode::new(b, a, 1024);
+        assert_approx_eq!(filtered_amplitude(&mut filter, 3000.0, 8000.0), 1.0, 0.05);
+    }
+
+    #[test]
+    fn test_biquad_bandpass_and_notch() {
+        let (b, a) = biquad_bandpass(1000.0, 2.0, 8000.0);
+        let mut filter = BiquadNode::new(b, a, 1024);
+        assert_approx_eq!(filtered_amplitude(&mut filter, 1000.0, 8000.0), 1.0, 0.01);
+        let (b, a) = biquad_notch(1000.0, 2.0, 8000.0);
+        let mut filter = BiquadNode::new(b, a, 1024);
+        assert!(filtered_amplitude(&mut filter, 1000.0, 8000.0) < 0.01);
+    }
+
+    #[test]
+    fn test_butterworth_lowpass() {
+        // 1024 points at 8 kHz gives 7.8125 Hz per bin. Cutoff 1000 Hz is at bin 128
+        let mut last_stopband = 0.0;
+        for &order in &[2, 3, 4, 6] {
+            let sections = butterworth_lowpass(order, 1000.0, 8000.0);
+            assert_eq!(sections.len(), order / 2 + order % 2);
+            let response = sos_response_db(&sections, 1024);
+            assert_approx_eq!(response[0], 0.0, 0.01);
+            assert_approx_eq!(response[128], -3.01, 0.05);
+            assert!(response[256] < last_stopband - 5.0);
+            last_stopband = response[256];
+        }
+    }
+
+    #[test]
+    fn test_butterworth_highpass() {
+        for &order in &[2, 5] {
+            let response = sos_response_db(&butterworth_highpass(order, 1000.0, 8000.0), 1024);
+            assert_approx_eq!(response[512], 0.0, 0.01);
+            assert_approx_eq!(response[128], -3.01, 0.05);
+        }
+    }
+
+    #[test]
+    fn test_butterworth_bandpass() {
+        // Sample rate equal to the size, so response bin i is at i Hz
+        for &order in &[1, 2, 3, 4] {
+            let sections = butterworth_bandpass(order, 500.0, 1500.0, 8192.0);
+            assert_eq!(sections.len(), order);
+            let response = sos_response_db(&sections, 8192);
+            assert_approx_eq!(response[500], -3.01, 0.05);
+            assert_approx_eq!(response[1500], -3.01, 0.05);
+            let peak = response[500..1500].iter().cloned().fold(f32::MIN, f32::max);
+            assert_approx_eq!(peak, 0.0, 0.05);
+            assert!(response[100] < -10.0 && response[3000] < -10.0);
+        }
+        // Wide band gives real poles in the transformed prototype
+        let response = sos_response_db(&butterworth_bandpass(3, 20.0, 3000.0, 8192.0), 8192);
+        assert_approx_eq!(response[20], -3.01, 0.05);
+        assert_approx_eq!(response[3000], -3.01, 0.05);
+    }
+
+    #[test]
+    fn test_moving_average_step() {
+        let mut gen = GenNode::new(Box::new(StepGen::new(0.5)), 8.0, 4);
+        let mut filter = MovingAverageNode::new(4, 4);
+        let mut output = filter.process(gen.next_frame()).clone();
+        output.extend(filter.process(gen.next_frame()));
+        output.extend(filter.process(gen.next_frame()));
+        let expected = [0.0, 0.0, 0.0, 0.0, 0.25, 0.5, 0.75, 1.0, 1.0, 1.0, 1.0, 1.0];
+        for i in 0..12 {
+            assert_approx_eq!(output[i], expected[i], 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_moving_average_partial_window() {
+        let mut filter = MovingAverageNode::new(4, 3);
+        assert_eq!(filter.process(&vec![3.0, 1.0, 2.0]), &vec![3.0, 2.0, 2.0]);
+        assert_eq!(filter.process(&vec![6.0, 1.0, 1.0]), &vec![3.0, 2.5, 2.5]);
+    }
+
+    #[test]
+    fn test_dc_block() {
+        let mut gen = GenNode::new(Box::new(SineGen::new(100.0)), 8000.0, 800);
+        let mut filter = DcBlockNode::new(0.995, 800);
+        let mut output = vec![];
+        for _ in 0..10 {
+            let signal: RealBuffer = gen.next_frame().iter().map(|x| x + 0.5).collect();
+            output = filter.process(&signal).clone();
+        }
+        let mean = output.iter().sum::<f32>() / output.len() as f32;
+        assert_approx_eq!(mean, 0.0, 1e-3);
+        let peak = output.iter().fold(0.0, |acc, x| f32::max(acc, x.abs()));
+        assert_approx_eq!(peak, 1.0, 0.02);
+    }
+
+    #[test]
+    fn test_comb_impulse_response() {
+        let mut filter = CombNode::new(5, -0.8, 8);
+        let mut response: RealBuffer = vec![];
+        let mut input = vec![0.0; 8];
+        input[0] = 1.0;
+        response.extend(filter.process(&input));
+        for _ in 0..3 {
+            response.extend(filter.process(&vec![0.0; 8]));
+        }
+        for (i, y) in response.iter().enumerate() {
+            if i % 5 == 0 {
+                assert_approx_eq!(y, (-0.8f32).powi(i as i32 / 5), 1e-6);
+            } else {
+                assert_eq!(*y, 0.0);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_comb_unstable() {
+        CombNode::new(5, 1.0, 8);
+    }
+
+    #[test]
+    fn test_fir_lowpass() {
+        let taps = fir_lowpass(65, 1000.0, 8000.0);
+        let mut filter = FirNode::new(taps.clone(), 1024);
+        assert_approx_eq!(filtered_amplitude(&mut filter, 100.0, 8000.0), 1.0, 0.01);
+        let mut filter = FirNode::new(taps, 1024);
+        assert!(filtered_amplitude(&mut filter, 2000.0, 8000.0) < 0.001);
+    }
+
+    #[test]
+    fn test_svf_outputs() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(3)), 44100.0, 256);
+        let mut filter = SvfNode::with_mode(2000.0, 2.0, 44100.0, SvfMode::Notch, 256);
+        for _ in 0..3 {
+            let input = gen.next_frame().clone();
+            let notch = filter.process(&input).clone();
+            for i in 0..256 {
+                let (lp, hp, bp) = (filter.lowpass()[i], filter.highpass()[i], filter.bandpass()[i]);
+                assert_approx_eq!(lp + hp, notch[i], 1e-5);
+                assert_approx_eq!(lp + hp + bp / 2.0, input[i], 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_svf_resonance() {
+        let q = 10.0;
+        let cutoff = 1000.0;
+        let amplitude = |freq: f32, mode: SvfMode| {
+            let mut filter = SvfNode::with_mode(cutoff, q, 44100.0, mode, 1024);
+            filtered_amplitude(&mut filter, freq, 44100.0)
+        };
+        let peak = amplitude(cutoff, SvfMode::Lowpass);
+        assert_approx_eq!(peak, q, 0.2);
+        assert!(peak > 5.0 * amplitude(0.5 * cutoff, SvfMode::Lowpass));
+        assert!(peak > 5.0 * amplitude(2.0 * cutoff, SvfMode::Lowpass));
+        assert_approx_eq!(amplitude(cutoff, SvfMode::Highpass), q, 0.2);
+        assert!(amplitude(cutoff, SvfMode::Notch) < 0.01);
+    }
+
+    #[test]
+    fn test_one_pole_step() {
+        let cutoff = 10.0;
+        let sample_rate = 1000.0;
+        let mut filter = OnePoleNode::new(cutoff, sample_rate, 8);
+        let mut output: RealBuffer = vec![];
+        for _ in 0..100 {
+            output.extend(filter.process(&vec![2.0; 8]));
+        }
+        // After one time constant the output is at 1 - 1/e of the final value
+        let tau = (sample_rate / (2.0 * PI * cutoff)).round() as usize;
+        assert_approx_eq!(output[tau - 1], 2.0 * (1.0 - (-1.0f32).exp()), 0.05);
+        assert!(output.windows(2).all(|w| w[1] >= w[0]));
+        assert_approx_eq!(output[799], 2.0, 1e-4);
+    }
+
+    #[test]
+    fn test_peaking_eq() {
+        // Sample rate equal to the size, so response bin i is at i Hz
+        let size = 8192;
+        let section = |(b, a): ([f32; 3], [f32; 3])| [[b[0], b[1], b[2], a[0], a[1], a[2]]];
+        let response = sos_response_db(&section(peaking_eq(1000.0, 1.0, 6.0, size as f32)), size);
+        assert_approx_eq!(response[1000], 6.0, 0.05);
+        assert_approx_eq!(response[0], 0.0, 0.05);
+        assert_approx_eq!(response[size / 2], 0.0, 0.05);
+        let cut = sos_response_db(&section(peaking_eq(1000.0, 1.0, -6.0, size as f32)), size);
+        assert_approx_eq!(cut[1000], -6.0, 0.05);
+    }
+
+    #[test]
+    fn test_shelves() {
+        let size = 8192;
+        let section = |(b, a): ([f32; 3], [f32; 3])| [[b[0], b[1], b[2], a[0], a[1], a[2]]];
+        let low = sos_response_db(&section(low_shelf(500.0, 0.707, 6.0, size as f32)), size);
+        assert_approx_eq!(low[0], 6.0, 0.05);
+        assert_approx_eq!(low[500], 3.0, 0.1);
+        assert_approx_eq!(low[size / 2], 0.0, 0.05);
+        let high = sos_response_db(&section(high_shelf(2000.0, 0.707, -6.0, size as f32)), size);
+        assert_approx_eq!(high[0], 0.0, 0.05);
+        assert_approx_eq!(high[2000], -3.0, 0.1);
+        assert_approx_eq!(high[size / 2], -6.0, 0.05);
+    }
+
+    #[test]
+    fn test_median_spikes() {
+        let window = 5;
+        let mut gen = GenNode::new(Box::new(SineGen::new(2.0)), 1000.0, 250);
+        let clean: RealBuffer = (0..4).flat_map(|_| gen.next_frame().clone()).collect();
+        let mut noisy = clean.clone();
+        for i in (37..1000).step_by(97) {
+            noisy[i] += if i % 2 == 0 { 5.0 } else { -5.0 };
+        }
+        let mut filter = MedianNode::new(window, 250);
+        let output: RealBuffer = noisy.chunks(250).flat_map(|frame| filter.process(&frame.to_vec()).clone()).collect();
+        // Median of a smooth signal is its delayed center sample
+        let delay = (window - 1) / 2;
+        for i in window..1000 {
+            assert_approx_eq!(output[i], clean[i - delay], 0.03);
+        }
+    }
+
+    #[test]
+    fn test_savgol_polynomial() {
+        let signal: RealBuffer = (0..20).map(|i| {
+            let x = i as f32 / 4.0;
+            x * x * x - 2.0 * x + 1.0
+        }).collect();
+        for (x, y) in savgol(&signal, 7, 3).iter().zip(&signal) {
+            assert_approx_eq!(x, y, 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_savgol_peak() {
+        let size = 101;
+        let width = 15;
+        let mut noise = GenNode::new(Box::new(WhiteNoiseGen::new(11)), 1.0, size);
+        let signal: RealBuffer = noise.next_frame().iter().enumerate()
+            .map(|(i, v)| (-((i as f32 - 50.0) / 5.0).powi(2) / 2.0).exp() + 0.05 * v)
+            .collect();
+        let smoothed = savgol(&signal, width, 2);
+        let boxcar: RealBuffer = (width / 2..size - width / 2)
+            .map(|i| signal[i - width / 2..=i + width / 2].iter().sum::<f32>() / width as f32)
+            .collect();
+        let savgol_peak = smoothed.iter().cloned().fold(0.0, f32::max);
+        let boxcar_peak = boxcar.iter().cloned().fold(0.0, f32::max);
+        assert!((1.0 - savgol_peak).abs() < 0.05);
+        assert!((1.0 - savgol_peak).abs() < (1.0 - boxcar_peak).abs());
+    }
+}
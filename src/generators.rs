@@ -8,19 +8,24 @@ use std::f32;
 use std::f32::consts::PI;
 use rand::distributions::{Normal, Distribution};
 
-use crate::{RealBuffer, SourceNode};
+use crate::{RealBuffer, SourceNode, ProcessingNode};
 
 
 /// This trait is implemented by node which is used to generate signals
 pub trait SignalGen {
 
-    /// Generate nth sample.
-    fn sample(&self, t: f32) -> f32;
+    /// Generate sample at time t (in seconds).
+    /// Generators with internal state (like noise) can update it here.
+    fn sample(&mut self, t: f32) -> f32;
 
     /// Function for checking if generator has next frame of data
     /// Return true if it has.
     fn has_next(&self) -> bool { true }
 
+    /// Restore generator internal state to the initial value.
+    /// Stateless generators don't need to implement it.
+    fn reset(&mut self) {}
+
 }
 
 
@@ -53,7 +58,7 @@ impl ImpulseGen {
 
 impl SignalGen for ImpulseGen {
 
-    fn sample(&self, t: f32) -> f32 {
+    fn sample(&mut self, t: f32) -> f32 {
         if t == self.impulse_pos { 1.0 } else { 0.0 }
     }
 }
@@ -87,11 +92,42 @@ impl StepGen {
 
 impl SignalGen for StepGen {
 
-    fn sample(&self, t: f32) -> f32 {
+    fn sample(&mut self, t: f32) -> f32 {
         if t >= self.step_pos { 1.0 } else { 0.0 }
     }
 }
 
+/// Constant (DC) signal
+/// x[n] = level
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::generators::{SignalGen, DCGen};
+/// 
+/// let mut gen = DCGen::new(0.5);
+/// assert_eq!(gen.sample(0.0), 0.5);
+/// assert_eq!(gen.sample(10.0), 0.5);
+/// ```
+pub struct DCGen {
+    level: f32,
+}
+
+impl DCGen {
+    /// Create new DC generator
+    ///   * level - Signal value
+    pub fn new(level: f32) -> DCGen {
+        DCGen { level }
+    }
+}
+
+impl SignalGen for DCGen {
+
+    fn sample(&mut self, _t: f32) -> f32 {
+        self.level
+    }
+}
+
 /// Sinusoidal signal
 /// 
 /// Example
@@ -121,13 +157,48 @@ impl SineGen {
 
 impl SignalGen for SineGen {
 
-    fn sample(&self, t: f32) -> f32 {
-        f32::sin(2.0 * PI * t * self.freq)
+    fn sample(&mut self, t: f32) -> f32 {
+        f32::sin(2.0 * PI * (t * self.freq).fract())
+    }
+}
+
+
+/// Cosine signal
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::generators::{SignalGen, CosineGen};
+/// 
+/// let mut gen = CosineGen::new(2.0);
+/// assert_approx_eq!(gen.sample(0.0), 1.0, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.125), 0.0, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.25), -1.0, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.375), 0.0, 1e-5f32);
+/// ```
+pub struct CosineGen {
+    freq: f32,
+}
+
+impl CosineGen {
+    /// Create a new Cosine generator with a given frequency.
+    ///   * freq - Frequency in Hz
+    pub fn new(freq: f32) -> CosineGen {
+        CosineGen { freq }
+    }
+}
+
+impl SignalGen for CosineGen {
+
+    fn sample(&mut self, t: f32) -> f32 {
+        f32::cos(2.0 * PI * (t * self.freq).fract())
     }
 }
 
 
 /// Generate triangular signal
+/// The signal starts at 0, reaches 1 at a quarter of the period and -1 at three quarters.
 /// 
 /// Example
 /// 
@@ -136,20 +207,19 @@ impl SignalGen for SineGen {
 /// use dsp::generators::{SignalGen, TriangleGen};
 /// 
 /// let mut gen = TriangleGen::new(2.0);
-/// assert_approx_eq!(gen.sample(0.0), -1.0, 1e-5f32);
-/// assert_approx_eq!(gen.sample(0.125), -0.5, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.0), 0.0, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.125), 1.0, 1e-5f32);
 /// assert_approx_eq!(gen.sample(0.25), 0.0, 1e-5f32);
-/// assert_approx_eq!(gen.sample(0.375), 0.5, 1e-5f32);
-/// assert_approx_eq!(gen.sample(0.5), -1.0, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.375), -1.0, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.5), 0.0, 1e-5f32);
 /// ```
 pub struct TriangleGen {
     freq: f32,
 }
 
 impl TriangleGen {
-    /// Create new Traingle generator
+    /// Create new Triangle generator
     ///   * freq - Frequency in Hz
-    ///   * sample_rate - How many samples per second
     pub fn new(freq: f32) -> TriangleGen {
         TriangleGen { freq }
     }
@@ -157,7 +227,50 @@ impl TriangleGen {
 
 impl SignalGen for TriangleGen {
 
-    fn sample(&self, t: f32) -> f32 {
+    fn sample(&mut self, t: f32) -> f32 {
+        let k = (t * self.freq).fract();
+        if k < 0.25 {
+            4.0 * k
+        } else if k < 0.75 {
+            2.0 - 4.0 * k
+        } else {
+            4.0 * k - 4.0
+        }
+    }
+}
+
+
+/// Generate sawtooth signal
+/// The signal rises linearly from -1 to 1 during each period and then resets.
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::generators::{SignalGen, SawtoothGen};
+/// 
+/// let mut gen = SawtoothGen::new(2.0);
+/// assert_approx_eq!(gen.sample(0.0), -1.0, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.125), -0.5, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.25), 0.0, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.375), 0.5, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.5), -1.0, 1e-5f32);
+/// ```
+pub struct SawtoothGen {
+    freq: f32,
+}
+
+impl SawtoothGen {
+    /// Create new Sawtooth generator
+    ///   * freq - Frequency in Hz
+    pub fn new(freq: f32) -> SawtoothGen {
+        SawtoothGen { freq }
+    }
+}
+
+impl SignalGen for SawtoothGen {
+
+    fn sample(&mut self, t: f32) -> f32 {
         let k = (t * self.freq).fract();
         2.0 * k - 1.0
     }
@@ -165,6 +278,7 @@ impl SignalGen for TriangleGen {
 
 
 /// Generate square signal
+/// The signal is +1 for the first `duty` part of each period and -1 for the rest.
 /// 
 /// Example
 /// 
@@ -172,7 +286,7 @@ impl SignalGen for TriangleGen {
 /// use assert_approx_eq::assert_approx_eq;
 /// use dsp::generators::{SignalGen, SquareGen};
 /// 
-/// let mut gen = SquareGen::new(2.0);
+/// let mut gen = SquareGen::new(2.0, 0.5);
 /// assert_approx_eq!(gen.sample(0.0), 1.0, 1e-5f32);
 /// assert_approx_eq!(gen.sample(0.125), 1.0, 1e-5f32);
 /// assert_approx_eq!(gen.sample(0.25), -1.0, 1e-5f32);
@@ -181,22 +295,67 @@ impl SignalGen for TriangleGen {
 /// ```
 pub struct SquareGen {
     freq: f32,
+    duty: f32,
 }
 
 impl SquareGen {
     /// Create square signal
     ///   * freq - Frequency in Hz
-    ///   * sample_rate - How many samples per second
-    pub fn new(freq: f32) -> SquareGen {
-        SquareGen { freq }
+    ///   * duty - Fraction of the period when signal is high (0.0..1.0). 0.5 gives symmetric wave
+    pub fn new(freq: f32, duty: f32) -> SquareGen {
+        SquareGen { freq, duty: duty.clamp(0.0, 1.0) }
     }
 }
 
 impl SignalGen for SquareGen {
 
-    fn sample(&self, t: f32) -> f32 {
+    fn sample(&mut self, t: f32) -> f32 {
         let k = (t * self.freq).fract();
-        if k < 0.5 { 1.0 } else { -1.0 }
+        if k < self.duty { 1.0 } else { -1.0 }
+    }
+}
+
+
+/// Play single cycle waveform stored in the table
+/// Values between table samples are linearly interpolated.
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::generators::{SignalGen, WavetableGen};
+/// 
+/// let mut gen = WavetableGen::new(vec![0.0, 1.0, 0.0, -1.0], 1.0);
+/// assert_approx_eq!(gen.sample(0.25), 1.0, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.375), 0.5, 1e-5f32);
+/// assert_approx_eq!(gen.sample(0.875), -0.5, 1e-5f32);
+/// ```
+pub struct WavetableGen {
+    table: RealBuffer,
+    freq: f32,
+}
+
+impl WavetableGen {
+    /// Create new Wavetable generator
+    ///   * table - Single period of the signal
+    ///   * freq - Frequency in Hz
+    pub fn new(table: RealBuffer, freq: f32) -> WavetableGen {
+        WavetableGen { table, freq }
+    }
+}
+
+impl SignalGen for WavetableGen {
+
+    fn sample(&mut self, t: f32) -> f32 {
+        let n = self.table.len();
+        if n == 0 {
+            return 0.0;
+        }
+        let pos = (t * self.freq).fract() * n as f32;
+        let idx = pos.floor() as usize % n;
+        let frac = pos - pos.floor();
+        let next = (idx + 1) % n;
+        self.table[idx] + frac * (self.table[next] - self.table[idx])
     }
 }
 
@@ -226,56 +385,267 @@ impl NoiseGen {
 
 impl SignalGen for NoiseGen {
 
-    fn sample(&self, _t: f32) -> f32 {
+    fn sample(&mut self, _t: f32) -> f32 {
         self.normal.sample(&mut rand::thread_rng()) as f32
     }
 }
 
 
+/// Generate uniform white noise in the range [-1, 1]
+/// Uses xorshift PRNG, so the same seed always gives the same sequence.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::generators::{SignalGen, WhiteNoiseGen};
+/// 
+/// let mut gen1 = WhiteNoiseGen::new(42);
+/// let mut gen2 = WhiteNoiseGen::new(42);
+/// assert_eq!(gen1.sample(0.0), gen2.sample(0.0));
+/// ```
+pub struct WhiteNoiseGen {
+    seed: u64,
+    state: u64,
+}
+
+impl WhiteNoiseGen {
+    /// Create white noise generator
+    ///   * seed - PRNG seed
+    pub fn new(seed: u64) -> WhiteNoiseGen {
+        // Xorshift state can't be 0
+        let seed = if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed };
+        WhiteNoiseGen { seed, state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+}
+
+impl SignalGen for WhiteNoiseGen {
+
+    fn sample(&mut self, _t: f32) -> f32 {
+        // Use top 24 bits, which is the f32 mantissa precision
+        let v = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        2.0 * v - 1.0
+    }
+
+    fn reset(&mut self) {
+        self.state = self.seed;
+    }
+}
+
+
+/// Generate pink (1/f) noise
+/// Based on Voss-McCartney algorithm: sum of several white noise sources,
+/// where source k is updated every 2^k samples.
+/// https://www.firstpr.com.au/dsp/pink-noise/
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::generators::{SignalGen, PinkNoiseGen};
+/// 
+/// let mut gen = PinkNoiseGen::new(42);
+/// let v = gen.sample(0.0);
+/// assert!(v >= -1.0 && v <= 1.0);
+/// ```
+pub struct PinkNoiseGen {
+    white: WhiteNoiseGen,
+    rows: Vec<f32>,
+    running_sum: f32,
+    counter: u32,
+}
+
+impl PinkNoiseGen {
+    /// Number of white noise sources. Covers 16 octaves of the spectrum.
+    const NUM_ROWS: usize = 16;
+
+    /// Create pink noise generator
+    ///   * seed - PRNG seed
+    pub fn new(seed: u64) -> PinkNoiseGen {
+        let mut white = WhiteNoiseGen::new(seed);
+        let rows: Vec<f32> = (0..PinkNoiseGen::NUM_ROWS).map(|_| white.sample(0.0)).collect();
+        let running_sum = rows.iter().sum();
+        PinkNoiseGen { white, rows, running_sum, counter: 0 }
+    }
+}
+
+impl SignalGen for PinkNoiseGen {
+
+    fn sample(&mut self, t: f32) -> f32 {
+        self.counter = self.counter.wrapping_add(1);
+        // Update the row given by the number of trailing zeros in the counter
+        let row = self.counter.trailing_zeros() as usize;
+        if row < self.rows.len() {
+            let v = self.white.sample(t);
+            self.running_sum += v - self.rows[row];
+            self.rows[row] = v;
+        }
+        let white = self.white.sample(t);
+        (self.running_sum + white) / (self.rows.len() + 1) as f32
+    }
+
+    fn reset(&mut self) {
+        self.white.reset();
+        for row in self.rows.iter_mut() {
+            *row = self.white.sample(0.0);
+        }
+        self.running_sum = self.rows.iter().sum();
+        self.counter = 0;
+    }
+}
+
+
+/// DTMF (Dual-tone multi-frequency) signal used in telephony
+/// Each key is represented by sum of two sinusoids.
+/// Characters not found on the keypad generate silence.
+/// https://en.wikipedia.org/wiki/Dual-tone_multi-frequency_signaling
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::generators::{SignalGen, DtmfGen};
+/// 
+/// let mut gen = DtmfGen::new('x');
+/// assert_eq!(gen.sample(0.1), 0.0);
+/// ```
+pub struct DtmfGen {
+    low_freq: f32,
+    high_freq: f32,
+}
+
+impl DtmfGen {
+    const LOW_FREQS: [f32; 4] = [697.0, 770.0, 852.0, 941.0];
+    const HIGH_FREQS: [f32; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+    const KEYPAD: [[char; 4]; 4] = [['1', '2', '3', 'A'],
+                                    ['4', '5', '6', 'B'],
+                                    ['7', '8', '9', 'C'],
+                                    ['*', '0', '#', 'D']];
+
+    /// Create DTMF generator for a given key
+    ///   * key - One of: 0-9, A-D, * or #
+    pub fn new(key: char) -> DtmfGen {
+        let key = key.to_ascii_uppercase();
+        for (row, keys) in DtmfGen::KEYPAD.iter().enumerate() {
+            if let Some(col) = keys.iter().position(|&k| k == key) {
+                return DtmfGen { low_freq: DtmfGen::LOW_FREQS[row], high_freq: DtmfGen::HIGH_FREQS[col] };
+            }
+        }
+        DtmfGen { low_freq: 0.0, high_freq: 0.0 }
+    }
+}
+
+impl SignalGen for DtmfGen {
+
+    fn sample(&mut self, t: f32) -> f32 {
+        let low = f32::sin(2.0 * PI * t * self.low_freq);
+        let high = f32::sin(2.0 * PI * t * self.high_freq);
+        0.5 * (low + high)
+    }
+}
+
+
+/// What the chirp does after the sweep time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChirpMode {
+    /// Signal is 0 after the sweep
+    Stop,
+    /// Continue with the end frequency
+    Hold,
+    /// Start the sweep again
+    Wrap,
+}
+
 /// A chirp is a signal in which frequency increases with time.
 /// Based on:
 /// https://en.wikipedia.org/wiki/Chirp#Linear
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::generators::{SignalGen, ChirpGen, ChirpMode};
+/// 
+/// let mut gen = ChirpGen::with_mode(1.0, 10.0, 1.0, ChirpMode::Wrap);
+/// assert_approx_eq!(gen.sample(0.3), gen.sample(1.3), 1e-4f32);
+/// ```
 pub struct ChirpGen {
     start_freq: f32,
     end_freq: f32,
     sweep_time: f32,
+    mode: ChirpMode,
 }
 
 impl ChirpGen {
-    /// Create chirp signal
+    /// Create chirp signal which stops after the sweep time
     ///   * start_freq - Start frequency in Hz
     ///   * end_freq - End frequency in Hz
-    ///   * length - in seconds
-    ///   * sample_rate - How many samples per second
+    ///   * sweep_time - in seconds
     pub fn new(start_freq: f32, end_freq: f32, sweep_time: f32) -> ChirpGen {
-        ChirpGen { start_freq, end_freq, sweep_time }
+        ChirpGen::with_mode(start_freq, end_freq, sweep_time, ChirpMode::Stop)
+    }
+
+    /// Create chirp signal
+    ///   * start_freq - Start frequency in Hz
+    ///   * end_freq - End frequency in Hz
+    ///   * sweep_time - in seconds
+    ///   * mode - What to do after the sweep time
+    pub fn with_mode(start_freq: f32, end_freq: f32, sweep_time: f32, mode: ChirpMode) -> ChirpGen {
+        ChirpGen { start_freq, end_freq, sweep_time, mode }
+    }
+
+    /// Phase in cycles at time t of the sweep
+    fn sweep_phase(&self, t: f32) -> f32 {
+        let c = (self.end_freq - self.start_freq) / self.sweep_time;
+        c / 2.0 * t.powi(2) + self.start_freq * t
     }
 }
 
 impl SignalGen for ChirpGen {
 
-    fn sample(&self, t: f32) -> f32 {
-        if t > self.sweep_time {
-            0.0
+    fn sample(&mut self, t: f32) -> f32 {
+        let phase = if t <= self.sweep_time {
+            self.sweep_phase(t)
         } else {
-            let c = (self.end_freq - self.start_freq) / (self.sweep_time);
-            let w = 2.0 * PI * (c/2.0*t.powi(2) + self.start_freq*t);
-            f32::sin(w)
-        }
+            match self.mode {
+                ChirpMode::Stop => return 0.0,
+                ChirpMode::Hold => {
+                    let end_phase = self.sweep_phase(self.sweep_time).fract();
+                    end_phase + self.end_freq * (t - self.sweep_time)
+                },
+                ChirpMode::Wrap => self.sweep_phase(t % self.sweep_time),
+            }
+        };
+        f32::sin(2.0 * PI * phase.fract())
     }
 }
 
 /// Create Source node based on generator
+/// The node keeps the index of the next sample, so consecutive frames
+/// form one continuous signal.
 pub struct GenNode {
-    gen: Box<SignalGen>,
+    gen: Box<dyn SignalGen>,
     output: RealBuffer,
     sample_freq: f32,
-    current_sample: f32,
+    // Integer counter, since f32 can't count past 2^24 samples
+    current_sample: u64,
 }
 
 impl GenNode {
-    pub fn new(gen: Box<SignalGen>, sample_freq: f32, buffer_size: usize) -> GenNode {
-        GenNode { gen, output: vec![0.0; buffer_size], sample_freq, current_sample: 0.0 }
+    pub fn new(gen: Box<dyn SignalGen>, sample_freq: f32, buffer_size: usize) -> GenNode {
+        GenNode { gen, output: vec![0.0; buffer_size], sample_freq, current_sample: 0 }
+    }
+
+    /// Start generating signal from the beginning
+    pub fn reset(&mut self) {
+        self.gen.reset();
+        self.current_sample = 0;
     }
 }
 
@@ -284,8 +654,189 @@ impl SourceNode for GenNode {
     
     fn next_frame(&mut self) -> &RealBuffer {
         for i in self.output.iter_mut() {
-            *i = self.gen.sample(self.current_sample / self.sample_freq);
-            self.current_sample += 1.0;
+            let t = (self.current_sample as f64 / self.sample_freq as f64) as f32;
+            *i = self.gen.sample(t);
+            self.current_sample += 1;
+        }
+        &self.output
+    }
+}
+
+/// Frequency modulation
+/// Modulator signal drives the instantaneous frequency of the sine carrier:
+/// f[n] = carrier_freq + index * modulator[n]
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::ProcessingNode;
+/// use dsp::generators::FmGen;
+/// 
+/// let mut fm = FmGen::new(1.0, 1.0, 4.0, 4);
+/// let modulator = vec![0.0, 0.0, 0.0, 0.0];
+/// let output = fm.process(&modulator);
+/// assert_approx_eq!(output[1], 1.0, 1e-5f32);
+/// ```
+pub struct FmGen {
+    carrier_freq: f32,
+    index: f32,
+    sample_freq: f32,
+    // Phase in cycles
+    phase: f64,
+    output: RealBuffer,
+}
+
+impl FmGen {
+    /// Create FM generator
+    ///   * carrier_freq - Carrier frequency in Hz
+    ///   * index - Frequency deviation in Hz for modulator value 1.0
+    ///   * sample_freq - How many samples per second
+    ///   * frame_size - Size of the output buffer
+    pub fn new(carrier_freq: f32, index: f32, sample_freq: f32, frame_size: usize) -> FmGen {
+        FmGen { carrier_freq, index, sample_freq, phase: 0.0, output: vec![0.0; frame_size] }
+    }
+}
+
+impl ProcessingNode for FmGen {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, modulator: &RealBuffer) -> &RealBuffer {
+        for (out, m) in self.output.iter_mut().zip(modulator) {
+            *out = f32::sin(2.0 * PI * self.phase as f32);
+            let freq = self.carrier_freq + self.index * m;
+            self.phase = (self.phase + (freq / self.sample_freq) as f64).fract();
+        }
+        &self.output
+    }
+}
+
+/// ADSR envelope stage
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AdsrStage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// ADSR (Attack, Decay, Sustain, Release) envelope
+/// Generates envelope level in range [0, 1], which can be used to shape the note.
+/// The envelope is started with note_on() and released with note_off().
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::SourceNode;
+/// use dsp::generators::AdsrNode;
+/// 
+/// let mut adsr = AdsrNode::new(0.5, 0.5, 0.5, 0.5, 4.0, 4);
+/// adsr.note_on();
+/// assert_eq!(adsr.next_frame(), &vec![0.5, 1.0, 0.75, 0.5]);
+/// adsr.note_off();
+/// assert_eq!(adsr.next_frame(), &vec![0.25, 0.0, 0.0, 0.0]);
+/// ```
+pub struct AdsrNode {
+    attack_step: f32,
+    decay_step: f32,
+    sustain_level: f32,
+    release_time: f32,
+    release_step: f32,
+    sample_freq: f32,
+    stage: AdsrStage,
+    level: f32,
+    output: RealBuffer,
+}
+
+impl AdsrNode {
+    /// Create ADSR envelope
+    ///   * attack_time - Time in seconds to rise from 0 to 1
+    ///   * decay_time - Time in seconds to fall from 1 to the sustain level
+    ///   * sustain_level - Level kept until the note is released
+    ///   * release_time - Time in seconds to fall from the sustain level to 0
+    ///   * sample_freq - How many samples per second
+    ///   * frame_size - Size of the output buffer
+    pub fn new(attack_time: f32, decay_time: f32, sustain_level: f32, release_time: f32,
+               sample_freq: f32, frame_size: usize) -> AdsrNode
+    {
+        let sustain_level = sustain_level.clamp(0.0, 1.0);
+        AdsrNode {
+            attack_step: AdsrNode::step(1.0, attack_time, sample_freq),
+            decay_step: AdsrNode::step(1.0 - sustain_level, decay_time, sample_freq),
+            sustain_level,
+            release_time,
+            release_step: 0.0,
+            sample_freq,
+            stage: AdsrStage::Idle,
+            level: 0.0,
+            output: vec![0.0; frame_size],
+        }
+    }
+
+    /// Level change per sample. Zero time means immediate change.
+    fn step(distance: f32, time: f32, sample_freq: f32) -> f32 {
+        let samples = time * sample_freq;
+        if samples > 0.0 { distance / samples } else { distance.max(f32::MIN_POSITIVE) }
+    }
+
+    /// Start attack stage
+    pub fn note_on(&mut self) {
+        self.stage = AdsrStage::Attack;
+    }
+
+    /// Start release stage from the current level
+    pub fn note_off(&mut self) {
+        if self.stage != AdsrStage::Idle {
+            // Scale release time, so the envelope decays with the same slope as from sustain level.
+            let distance = if self.sustain_level > 0.0 { self.level / self.sustain_level } else { 1.0 };
+            self.release_step = AdsrNode::step(self.level, distance * self.release_time, self.sample_freq);
+            self.stage = AdsrStage::Release;
+        }
+    }
+
+    /// Check if envelope is finished
+    pub fn is_idle(&self) -> bool {
+        self.stage == AdsrStage::Idle
+    }
+
+    fn next_level(&mut self) -> f32 {
+        match self.stage {
+            AdsrStage::Idle => self.level = 0.0,
+            AdsrStage::Attack => {
+                self.level += self.attack_step;
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = AdsrStage::Decay;
+                }
+            },
+            AdsrStage::Decay => {
+                self.level -= self.decay_step;
+                if self.level <= self.sustain_level {
+                    self.level = self.sustain_level;
+                    self.stage = AdsrStage::Sustain;
+                }
+            },
+            AdsrStage::Sustain => self.level = self.sustain_level,
+            AdsrStage::Release => {
+                self.level -= self.release_step;
+                if self.level <= 0.0 {
+                    self.level = 0.0;
+                    self.stage = AdsrStage::Idle;
+                }
+            },
+        }
+        self.level
+    }
+}
+
+impl SourceNode for AdsrNode {
+    type Buffer = RealBuffer;
+
+    fn next_frame(&mut self) -> &RealBuffer {
+        for i in 0..self.output.len() {
+            self.output[i] = self.next_level();
         }
         &self.output
     }
@@ -296,17 +847,267 @@ impl SourceNode for GenNode {
 /// ------------------------------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use crate::num_complex::Complex32;
+    use crate::fft::ForwardFFT;
     use super::*;
 
     #[test]
     fn test_impulse() {
-        let gen = ImpulseGen::new(0.2);
+        let mut gen = ImpulseGen::new(0.2);
         assert_eq!(gen.sample(0.0), 0.0);
         assert_eq!(gen.sample(0.1), 0.0);
         assert_eq!(gen.sample(0.2), 1.0);
         assert_eq!(gen.sample(0.3), 0.0);
     }
     
+    #[test]
+    fn test_square_duty() {
+        let mut node = GenNode::new(Box::new(SquareGen::new(1.0, 0.25)), 8.0, 8);
+        let expected = vec![1.0, 1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0];
+        assert_eq!(node.next_frame(), &expected);
+
+        let mut node = GenNode::new(Box::new(SquareGen::new(1.0, 0.5)), 8.0, 8);
+        let expected = vec![1.0, 1.0, 1.0, 1.0, -1.0, -1.0, -1.0, -1.0];
+        assert_eq!(node.next_frame(), &expected);
+
+        let mut node = GenNode::new(Box::new(SquareGen::new(1.0, 0.75)), 8.0, 8);
+        let expected = vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, -1.0, -1.0];
+        assert_eq!(node.next_frame(), &expected);
+    }
+
+    #[test]
+    fn test_square_duty_clamped() {
+        let mut gen = SquareGen::new(1.0, 1.5);
+        assert_eq!(gen.sample(0.9), 1.0);
+        let mut gen = SquareGen::new(1.0, -0.5);
+        assert_eq!(gen.sample(0.0), -1.0);
+    }
+
+    #[test]
+    fn test_dc() {
+        let mut node = GenNode::new(Box::new(DCGen::new(-0.25)), 8.0, 8);
+        node.next_frame();
+        assert_eq!(node.next_frame(), &vec![-0.25; 8]);
+    }
+
+    #[test]
+    fn test_cosine() {
+        let mut cos_node = GenNode::new(Box::new(CosineGen::new(2.0)), 16.0, 16);
+        let mut sin_node = GenNode::new(Box::new(SineGen::new(2.0)), 16.0, 20);
+        let cosine = cos_node.next_frame().clone();
+        let sine = sin_node.next_frame();
+        assert_eq!(cosine[0], 1.0);
+        // Cosine is a sine shifted by quarter period (2 samples)
+        for i in 0..16 {
+            assert_approx_eq!(cosine[i], sine[i+2], 1e-5f32);
+        }
+    }
+
+    #[test]
+    fn test_triangle() {
+        let mut node = GenNode::new(Box::new(TriangleGen::new(1.0)), 8.0, 8);
+        let output = node.next_frame();
+        let expected = [0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0, -0.5];
+        for i in 0..8 {
+            assert_approx_eq!(output[i], expected[i], 1e-5f32);
+        }
+        // Constant slope on each half of the period
+        let mut gen = TriangleGen::new(1.0);
+        for i in 0..10 {
+            let t = -0.25 + 0.05 * i as f32;
+            let rising = gen.sample(t + 1.05) - gen.sample(t + 1.0);
+            let falling = gen.sample(t + 1.55) - gen.sample(t + 1.5);
+            assert_approx_eq!(rising, 0.2, 1e-4f32);
+            assert_approx_eq!(falling, -0.2, 1e-4f32);
+        }
+    }
+
+    #[test]
+    fn test_sawtooth() {
+        let mut gen = SawtoothGen::new(1.0);
+        assert_approx_eq!(gen.sample(0.5), 0.0, 1e-5f32);
+        assert_approx_eq!(gen.sample(1.5), 0.0, 1e-5f32);
+
+        // Consecutive frames should join with the same step as within the frame
+        let mut node = GenNode::new(Box::new(SawtoothGen::new(1.0)), 16.0, 4);
+        let frame1 = node.next_frame().clone();
+        let frame2 = node.next_frame().clone();
+        assert_approx_eq!(frame1[1] - frame1[0], 0.125, 1e-5f32);
+        assert_approx_eq!(frame2[0] - frame1[3], 0.125, 1e-5f32);
+    }
+
+    #[test]
+    fn test_wavetable() {
+        let table = vec![0.0, 1.0, 0.0, -1.0];
+        let mut node = GenNode::new(Box::new(WavetableGen::new(table, 1.0)), 8.0, 8);
+        let output = node.next_frame();
+        let expected = [0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0, -0.5];
+        for i in 0..8 {
+            assert_approx_eq!(output[i], expected[i], 1e-5f32);
+        }
+    }
+
+    #[test]
+    fn test_white_noise_seed() {
+        let mut node1 = GenNode::new(Box::new(WhiteNoiseGen::new(1234)), 8.0, 64);
+        let mut node2 = GenNode::new(Box::new(WhiteNoiseGen::new(1234)), 8.0, 64);
+        let mut node3 = GenNode::new(Box::new(WhiteNoiseGen::new(4321)), 8.0, 64);
+        for _ in 0..3 {
+            let frame1 = node1.next_frame().clone();
+            let frame2 = node2.next_frame().clone();
+            let frame3 = node3.next_frame().clone();
+            assert_eq!(frame1, frame2);
+            assert_ne!(frame1, frame3);
+            assert!(frame1.iter().all(|&v| (-1.0..=1.0).contains(&v)));
+        }
+    }
+
+    #[test]
+    fn test_pink_noise_spectrum() {
+        let size = 1 << 16;
+        let mut node = GenNode::new(Box::new(PinkNoiseGen::new(7)), 1.0, size);
+        let mut input: Vec<Complex32> = node.next_frame().iter().map(|&v| Complex32::new(v, 0.0)).collect();
+        let mut spectrum = vec![Complex32::new(0.0, 0.0); size];
+        ForwardFFT::new(size).process(&mut input, &mut spectrum);
+        let band_power = |start: usize| -> f32 {
+            spectrum[start..2*start].iter().map(|c| c.norm_sqr()).sum()
+        };
+        // For 1/f noise each octave should have the same power
+        let low = band_power(64);
+        let high = band_power(8192);
+        assert!(low / high > 0.5 && low / high < 2.0, "ratio: {}", low / high);
+    }
+
+    #[test]
+    fn test_dtmf() {
+        let size = 8000;
+        let mut node = GenNode::new(Box::new(DtmfGen::new('5')), 8000.0, size);
+        let mut input: Vec<Complex32> = node.next_frame().iter().map(|&v| Complex32::new(v, 0.0)).collect();
+        let mut spectrum = vec![Complex32::new(0.0, 0.0); size];
+        ForwardFFT::new(size).process(&mut input, &mut spectrum);
+        // With 1 second of signal each bin is 1Hz wide
+        let mut bins: Vec<usize> = (0..size/2).collect();
+        bins.sort_by(|&a, &b| spectrum[b].norm().partial_cmp(&spectrum[a].norm()).unwrap());
+        let mut peaks = vec![bins[0], bins[1]];
+        peaks.sort();
+        assert_eq!(peaks, vec![770, 1336]);
+    }
+
+    /// Estimate frequency from the zero crossings of the signal
+    fn crossing_freq(xs: &[f32], sample_rate: f32) -> f32 {
+        let crossings: Vec<f32> = (1..xs.len())
+            .filter(|&i| xs[i-1] < 0.0 && xs[i] >= 0.0 || xs[i-1] >= 0.0 && xs[i] < 0.0)
+            .map(|i| (i - 1) as f32 + xs[i-1] / (xs[i-1] - xs[i]))
+            .collect();
+        let span = (crossings[crossings.len()-1] - crossings[0]) / sample_rate;
+        (crossings.len() - 1) as f32 / (2.0 * span)
+    }
+
+    #[test]
+    fn test_chirp_freq() {
+        let sample_rate = 10_000.0;
+        let gen = ChirpGen::with_mode(100.0, 200.0, 10.0, ChirpMode::Hold);
+        let mut node = GenNode::new(Box::new(gen), sample_rate, 1000);
+        let signal: Vec<f32> = (0..110).flat_map(|_| node.next_frame().clone()).collect();
+        // Windows of 50ms at the start, before and after the end of the sweep
+        let start = crossing_freq(&signal[0..500], sample_rate);
+        let end = crossing_freq(&signal[99_500..100_000], sample_rate);
+        let hold = crossing_freq(&signal[100_000..109_000], sample_rate);
+        assert_approx_eq!(start, 100.25, 0.5);
+        assert_approx_eq!(end, 199.75, 0.5);
+        assert_approx_eq!(hold, 200.0, 0.5);
+    }
+
+    #[test]
+    fn test_chirp_mode() {
+        let mut gen = ChirpGen::new(1.0, 10.0, 1.0);
+        assert_eq!(gen.sample(1.5), 0.0);
+        let mut gen = ChirpGen::with_mode(1.0, 10.0, 1.0, ChirpMode::Wrap);
+        assert_approx_eq!(gen.sample(1.125), gen.sample(0.125), 1e-4f32);
+    }
+
+    #[test]
+    fn test_gen_node_continuous() {
+        // 0.3 period per frame, so frames don't start at the same phase
+        let mut node = GenNode::new(Box::new(SineGen::new(3.0)), 100.0, 10);
+        let mut signal = node.next_frame().clone();
+        signal.extend(node.next_frame());
+        let max_step = 2.0 * PI * 3.0 / 100.0;
+        for i in 1..signal.len()-1 {
+            let d1 = signal[i] - signal[i-1];
+            let d2 = signal[i+1] - signal[i];
+            assert!(d1.abs() <= max_step);
+            assert!((d2 - d1).abs() <= max_step.powi(2), "Discontinuity at {}", i);
+        }
+        let expected = SineGen::new(3.0).sample(0.19);
+        assert_approx_eq!(signal[19], expected, 1e-5f32);
+    }
+
+    #[test]
+    fn test_gen_node_reset() {
+        let mut node = GenNode::new(Box::new(PinkNoiseGen::new(3)), 100.0, 64);
+        let first = node.next_frame().clone();
+        node.next_frame();
+        node.reset();
+        assert_eq!(node.next_frame(), &first);
+
+        let mut node = GenNode::new(Box::new(SineGen::new(3.0)), 100.0, 64);
+        let first = node.next_frame().clone();
+        node.next_frame();
+        node.reset();
+        assert_eq!(node.next_frame(), &first);
+    }
+
+    #[test]
+    fn test_fm_no_modulation() {
+        let mut fm = FmGen::new(5.0, 3.0, 64.0, 32);
+        let mut node = GenNode::new(Box::new(SineGen::new(5.0)), 64.0, 32);
+        let modulator = vec![0.0; 32];
+        for _ in 0..3 {
+            let expected = node.next_frame();
+            let output = fm.process(&modulator);
+            for i in 0..32 {
+                assert_approx_eq!(output[i], expected[i], 1e-4f32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_adsr() {
+        let mut adsr = AdsrNode::new(0.1, 0.2, 0.5, 0.4, 100.0, 10);
+        adsr.note_on();
+        let attack = adsr.next_frame().clone();
+        assert_approx_eq!(attack[0], 0.1, 1e-5f32);
+        assert_approx_eq!(attack[9], 1.0, 1e-5f32);
+        let decay = adsr.next_frame().clone();
+        assert_approx_eq!(decay[4], 0.875, 1e-5f32);
+        let decay = adsr.next_frame();
+        assert_approx_eq!(decay[9], 0.5, 1e-5f32);
+        let sustain = adsr.next_frame();
+        assert_eq!(sustain, &vec![0.5; 10]);
+        adsr.note_off();
+        let release = adsr.next_frame();
+        assert_approx_eq!(release[0], 0.5 - 0.5 / 40.0, 1e-5f32);
+        for _ in 0..4 {
+            adsr.next_frame();
+        }
+        assert!(adsr.is_idle());
+        assert_eq!(adsr.next_frame(), &vec![0.0; 10]);
+    }
+
+    #[test]
+    fn test_adsr_release_during_attack() {
+        let mut adsr = AdsrNode::new(0.1, 0.1, 0.5, 0.1, 100.0, 5);
+        adsr.note_on();
+        let attack = adsr.next_frame().clone();
+        adsr.note_off();
+        let release = adsr.next_frame();
+        // Release starts from the current level
+        assert!(release[0] < attack[4]);
+        assert!(attack[4] - release[0] < 0.1);
+    }
+
     #[test]
     fn test_gen_node() {
         let mut node = GenNode::new(Box::new(ImpulseGen::new(0.2)), 10.0, 4);
@@ -0,0 +1,458 @@
+//! Read and write signals
+//!
+//! Supported audio format is mono 16-bit PCM WAV.
+//! Samples are converted to f32 in range [-1, 1).
+//! Buffers can also be exported to CSV for plotting in external tools.
+//!
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use crate::{RealBuffer, SourceNode, SinkNode};
+
+
+// Size of the RIFF header with fmt chunk and data chunk header
+const WAV_HEADER_SIZE: u32 = 44;
+
+
+/// Read mono 16-bit WAV in frames.
+/// The last frame can be shorter. After the end of data the frames are empty.
+///
+/// Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use dsp::{SinkNode, SourceNode};
+/// use dsp::io::{WavSink, WavSource};
+///
+/// let mut sink = WavSink::new(Cursor::new(vec![]), 8000).unwrap();
+/// sink.consume(&vec![0.5, -0.5, 0.25]);
+/// let data = sink.finalize().unwrap().into_inner();
+///
+/// let mut source = WavSource::new(Cursor::new(data), 2).unwrap();
+/// assert_eq!(source.sample_rate(), 8000);
+/// assert_eq!(source.next_frame(), &vec![0.5, -0.5]);
+/// assert_eq!(source.next_frame(), &vec![0.25]);
+/// assert!(source.next_frame().is_empty());
+/// ```
+pub struct WavSource<R: Read> {
+    reader: R,
+    sample_rate: u32,
+    // Samples left in the data chunk
+    remaining: usize,
+    frame_size: usize,
+    output: RealBuffer,
+}
+
+impl WavSource<BufReader<File>> {
+    /// Open WAV file
+    pub fn open<P: AsRef<Path>>(path: P, frame_size: usize) -> io::Result<WavSource<BufReader<File>>> {
+        WavSource::new(BufReader::new(File::open(path)?), frame_size)
+    }
+}
+
+impl<R: Read> WavSource<R> {
+    /// Read WAV header from the reader.
+    /// Returns InvalidData error if the data is not mono 16-bit PCM WAV.
+    pub fn new(mut reader: R, frame_size: usize) -> io::Result<WavSource<R>> {
+        let mut riff = [0u8; 12];
+        reader.read_exact(&mut riff)?;
+        if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+            return Err(invalid_data("Not a WAV file"));
+        }
+        let mut sample_rate = None;
+        loop {
+            let mut chunk = [0u8; 8];
+            reader.read_exact(&mut chunk)?;
+            let size = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+            match &chunk[0..4] {
+                b"fmt " => {
+                    if size < 16 {
+                        return Err(invalid_data("Invalid format chunk"));
+                    }
+                    // Only the first 16 bytes are used, extension is skipped
+                    let mut fmt = [0u8; 16];
+                    reader.read_exact(&mut fmt)?;
+                    io::copy(&mut (&mut reader).take(size as u64 - 16), &mut io::sink())?;
+                    let format = u16::from_le_bytes([fmt[0], fmt[1]]);
+                    let channels = u16::from_le_bytes([fmt[2], fmt[3]]);
+                    let bits = u16::from_le_bytes([fmt[14], fmt[15]]);
+                    if format != 1 || channels != 1 || bits != 16 {
+                        return Err(invalid_data("Only mono 16-bit PCM is supported"));
+                    }
+                    sample_rate = Some(u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]));
+                    skip_padding(&mut reader, size)?;
+                }
+                b"data" => {
+                    let sample_rate = sample_rate.ok_or_else(|| invalid_data("Missing format chunk"))?;
+                    return Ok(WavSource {
+                        reader,
+                        sample_rate,
+                        remaining: size as usize / 2,
+                        frame_size,
+                        output: Vec::with_capacity(frame_size),
+                    });
+                }
+                _ => {
+                    // Skip unknown chunks (e.g. LIST)
+                    io::copy(&mut (&mut reader).take(size as u64), &mut io::sink())?;
+                    skip_padding(&mut reader, size)?;
+                }
+            }
+        }
+    }
+
+    /// Sample rate from the file header
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Number of samples which were not read yet
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<R: Read> SourceNode for WavSource<R> {
+    type Buffer = RealBuffer;
+
+    /// Next frame. Truncated file is treated as the end of data
+    fn next_frame(&mut self) -> &RealBuffer {
+        let n = usize::min(self.frame_size, self.remaining);
+        let mut bytes = vec![0u8; 2 * n];
+        let read = read_fully(&mut self.reader, &mut bytes);
+        self.remaining = if read == bytes.len() { self.remaining - n } else { 0 };
+        self.output.clear();
+        self.output.extend(bytes[..read - read % 2].chunks(2)
+            .map(|b| i16_to_sample(i16::from_le_bytes([b[0], b[1]]))));
+        &self.output
+    }
+}
+
+
+/// Write signal to mono 16-bit WAV.
+/// Samples are clipped to range [-1, 1].
+/// The header is updated when the sink is finalized or dropped.
+/// Frames which would make the data exceed the 4 GiB RIFF limit are rejected with InvalidInput error.
+pub struct WavSink<W: Write + Seek> {
+    writer: Option<W>,
+    num_samples: u32,
+    error: Option<io::Error>,
+}
+
+impl WavSink<BufWriter<File>> {
+    /// Create WAV file
+    pub fn create<P: AsRef<Path>>(path: P, sample_rate: u32) -> io::Result<WavSink<BufWriter<File>>> {
+        WavSink::new(BufWriter::new(File::create(path)?), sample_rate)
+    }
+}
+
+impl<W: Write + Seek> WavSink<W> {
+    /// Write WAV header to the writer
+    pub fn new(mut writer: W, sample_rate: u32) -> io::Result<WavSink<W>> {
+        let mut header = vec![];
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&(WAV_HEADER_SIZE - 8).to_le_bytes());
+        header.extend_from_slice(b"WAVEfmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&sample_rate.to_le_bytes());
+        header.extend_from_slice(&(2 * sample_rate).to_le_bytes());
+        header.extend_from_slice(&2u16.to_le_bytes());
+        header.extend_from_slice(&16u16.to_le_bytes());
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&0u32.to_le_bytes());
+        writer.write_all(&header)?;
+        Ok(WavSink { writer: Some(writer), num_samples: 0, error: None })
+    }
+
+    /// Update the header and return the writer.
+    /// Returns the first error which happened while consuming frames.
+    pub fn finalize(mut self) -> io::Result<W> {
+        self.write_sizes()?;
+        Ok(self.writer.take().unwrap())
+    }
+
+    fn write_sizes(&mut self) -> io::Result<()> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => return Ok(()),
+        };
+        // consume never lets the sizes overflow
+        let (riff_size, data_size) = riff_sizes(self.num_samples).unwrap();
+        writer.seek(SeekFrom::Start(4))?;
+        writer.write_all(&riff_size.to_le_bytes())?;
+        writer.seek(SeekFrom::Start(WAV_HEADER_SIZE as u64 - 4))?;
+        writer.write_all(&data_size.to_le_bytes())?;
+        writer.seek(SeekFrom::End(0))?;
+        writer.flush()
+    }
+}
+
+impl<W: Write + Seek> SinkNode for WavSink<W> {
+    type Buffer = RealBuffer;
+
+    fn consume(&mut self, input: &RealBuffer) {
+        if self.error.is_some() {
+            return;
+        }
+        let num_samples = match (input.len() as u64).checked_add(self.num_samples as u64) {
+            Some(n) if n <= u32::MAX as u64 && riff_sizes(n as u32).is_some() => n as u32,
+            _ => {
+                self.error = Some(io::Error::new(io::ErrorKind::InvalidInput, "WAV data exceeds 4 GiB limit"));
+                return;
+            }
+        };
+        let bytes: Vec<u8> = input.iter()
+            .flat_map(|&x| sample_to_i16(x).to_le_bytes().to_vec())
+            .collect();
+        if let Some(writer) = self.writer.as_mut() {
+            match writer.write_all(&bytes) {
+                Ok(()) => self.num_samples = num_samples,
+                Err(err) => self.error = Some(err),
+            }
+        }
+    }
+}
+
+impl<W: Write + Seek> Drop for WavSink<W> {
+    fn drop(&mut self) {
+        let _ = self.write_sizes();
+    }
+}
+
+
+/// Write signal to CSV file. One sample per row, with the header "value"
+pub fn write_csv<P: AsRef<Path>>(path: P, buf: &RealBuffer) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "value")?;
+    for x in buf {
+        writeln!(writer, "{}", x)?;
+    }
+    writer.flush()
+}
+
+/// Write spectrum to CSV file with the header "frequency,magnitude".
+/// Returns InvalidInput error if the buffers have different lengths.
+pub fn write_spectrum_csv<P: AsRef<Path>>(path: P, freqs: &RealBuffer, mags: &RealBuffer) -> io::Result<()> {
+    if freqs.len() != mags.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Frequencies and magnitudes have different lengths"));
+    }
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "frequency,magnitude")?;
+    for (f, m) in freqs.iter().zip(mags) {
+        writeln!(writer, "{},{}", f, m)?;
+    }
+    writer.flush()
+}
+
+
+/// Convert integer samples to float. Samples are divided by 32768, so the range is [-1, 1)
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::io::i16_to_f32;
+/// 
+/// assert_eq!(i16_to_f32(&[-32768, 0, 16384]), vec![-1.0, 0.0, 0.5]);
+/// ```
+pub fn i16_to_f32(samples: &[i16]) -> RealBuffer {
+    samples.iter().map(|&v| i16_to_sample(v)).collect()
+}
+
+/// Convert float samples to integer. Samples are multiplied by 32768 and
+/// values out of the i16 range are saturated (no wraparound)
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::io::f32_to_i16;
+/// 
+/// assert_eq!(f32_to_i16(&vec![-1.0, 0.5, 1.0]), vec![-32768, 16384, 32767]);
+/// ```
+pub fn f32_to_i16(buf: &RealBuffer) -> Vec<i16> {
+    buf.iter().map(|&x| sample_to_i16(x)).collect()
+}
+
+
+fn i16_to_sample(v: i16) -> f32 {
+    v as f32 / 32768.0
+}
+
+fn sample_to_i16(x: f32) -> i16 {
+    (x * 32768.0).round().clamp(-32768.0, 32767.0) as i16
+}
+
+// RIFF chunk size and data chunk size for the given number of samples.
+// None if they don't fit into the 32-bit header fields.
+fn riff_sizes(num_samples: u32) -> Option<(u32, u32)> {
+    let data_size = num_samples.checked_mul(2)?;
+    Some(((WAV_HEADER_SIZE - 8).checked_add(data_size)?, data_size))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// Chunks with odd size are padded with one byte
+fn skip_padding<R: Read>(reader: &mut R, size: u32) -> io::Result<()> {
+    if size % 2 == 1 {
+        reader.read_exact(&mut [0u8; 1])?;
+    }
+    Ok(())
+}
+
+// Read as many bytes as possible. Returns number of bytes read
+fn read_fully<R: Read>(reader: &mut R, buf: &mut [u8]) -> usize {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        }
+    }
+    read
+}
+
+
+/// ------------------------------------------------------------------------------------------------
+/// Module unit tests
+/// ------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::generators::{GenNode, SineGen};
+    use super::*;
+
+    #[test]
+    fn test_wav_round_trip() {
+        let path = std::env::temp_dir().join("dsp_test_wav_round_trip.wav");
+        let mut gen = GenNode::new(Box::new(SineGen::new(440.0)), 8000.0, 100);
+        let mut expected = vec![];
+        {
+            let mut sink = WavSink::create(&path, 8000).unwrap();
+            for _ in 0..3 {
+                let frame = gen.next_frame();
+                expected.extend_from_slice(frame);
+                sink.consume(frame);
+            }
+            sink.finalize().unwrap();
+        }
+        let mut source = WavSource::open(&path, 128).unwrap();
+        assert_eq!(source.sample_rate(), 8000);
+        assert_eq!(source.remaining(), 300);
+        let mut samples = source.next_frame().clone();
+        samples.extend(source.next_frame());
+        assert_eq!(source.next_frame().len(), 300 - 256);
+        assert!(source.next_frame().is_empty());
+        for (x, y) in samples.iter().zip(&expected) {
+            assert_approx_eq!(x, y, 2.0 / 32768.0);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_wav_sink_on_drop() {
+        let mut data = vec![];
+        {
+            let mut sink = WavSink::new(Cursor::new(&mut data), 44100).unwrap();
+            sink.consume(&vec![1.0, -1.0, 2.0]);
+        }
+        assert_eq!(data.len(), 44 + 6);
+        let mut source = WavSource::new(Cursor::new(data), 4).unwrap();
+        assert_eq!(source.next_frame(), &vec![32767.0 / 32768.0, -1.0, 32767.0 / 32768.0]);
+    }
+
+    #[test]
+    fn test_wav_source_invalid() {
+        assert!(WavSource::new(Cursor::new(b"RIFX0000WAVE".to_vec()), 4).is_err());
+        // Stereo file
+        let mut data = vec![];
+        WavSink::new(Cursor::new(&mut data), 8000).unwrap().finalize().unwrap();
+        data[22] = 2;
+        let err = WavSource::new(Cursor::new(data), 4).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_wav_source_fmt_size() {
+        let mut data = vec![];
+        {
+            let mut sink = WavSink::new(Cursor::new(&mut data), 8000).unwrap();
+            sink.consume(&vec![0.5, -0.5]);
+        }
+        // Format chunk with 2 bytes of extension
+        let mut extended = data.clone();
+        extended[16..20].copy_from_slice(&18u32.to_le_bytes());
+        extended.splice(36..36, vec![0, 0]);
+        let mut source = WavSource::new(Cursor::new(extended), 4).unwrap();
+        assert_eq!(source.next_frame(), &vec![0.5, -0.5]);
+        // Too short
+        let mut short = data.clone();
+        short[16..20].copy_from_slice(&14u32.to_le_bytes());
+        let err = WavSource::new(Cursor::new(short), 4).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // Size larger than the file is not allocated upfront
+        let mut huge = data.clone();
+        huge[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = WavSource::new(Cursor::new(huge), 4).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_wav_sink_size_limit() {
+        let mut data = vec![];
+        let mut sink = WavSink::new(Cursor::new(&mut data), 8000).unwrap();
+        sink.consume(&vec![0.5; 4]);
+        // Pretend almost 4 GiB were already written
+        sink.num_samples = (u32::MAX - (WAV_HEADER_SIZE - 8)) / 2 - 2;
+        sink.consume(&vec![0.5; 4]);
+        let err = sink.finalize().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(data.len(), 44 + 8);
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let path = std::env::temp_dir().join("dsp_test_write_csv.csv");
+        let signal = vec![0.5, -0.25, 1.0 / 3.0];
+        write_csv(&path, &signal).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("value"));
+        let values: RealBuffer = lines.map(|l| l.parse().unwrap()).collect();
+        assert_eq!(values, signal);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_spectrum_csv() {
+        let path = std::env::temp_dir().join("dsp_test_write_spectrum_csv.csv");
+        write_spectrum_csv(&path, &vec![0.0, 10.0], &vec![1.5, 0.25]).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let rows: Vec<Vec<f32>> = text.lines()
+            .skip(1)
+            .map(|l| l.split(',').map(|v| v.parse().unwrap()).collect())
+            .collect();
+        assert!(text.starts_with("frequency,magnitude\n"));
+        assert_eq!(rows, vec![vec![0.0, 1.5], vec![10.0, 0.25]]);
+        assert!(write_spectrum_csv(&path, &vec![0.0], &vec![]).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sample_conversion() {
+        assert_eq!(i16_to_f32(&[i16::MIN, i16::MAX]), vec![-1.0, 32767.0 / 32768.0]);
+        assert_eq!(f32_to_i16(&vec![-1.0, 1.0]), vec![i16::MIN, i16::MAX]);
+        assert_eq!(f32_to_i16(&vec![-1.5, 1.5]), vec![i16::MIN, i16::MAX]);
+        let samples: Vec<i16> = vec![-12345, -1, 0, 1, 23456];
+        assert_eq!(f32_to_i16(&i16_to_f32(&samples)), samples);
+    }
+}
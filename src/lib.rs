@@ -3,14 +3,23 @@
 //! Signals can be processed in Time or Frequency domain
 //!
 
+pub mod buffers;
 pub mod generators;
 pub mod signals;
 pub mod fft;
+pub mod filters;
+pub mod io;
+pub mod multichannel;
+#[cfg(feature = "serde")]
+pub mod serialization;
 pub mod spectrums;
 pub mod windows;
 mod vectors;
 
 pub use num_complex;
+use std::fmt;
+use rustfft::FFTnum;
+use rustfft::num_traits::Float;
 use crate::num_complex::Complex32;
 
 
@@ -20,6 +29,39 @@ pub type RealBuffer = Vec<f32>;
 /// Frequency domain data buffer based on complex numbers
 pub type ComplexBuffer = Vec<Complex32>;
 
+/// Numeric type of the signal samples. Implemented for f32 and f64
+pub trait Sample: FFTnum + Float + Default + fmt::Debug {}
+
+impl Sample for f32 {}
+impl Sample for f64 {}
+
+/// Errors reported by the checked (try_*) processing functions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DspError {
+    /// Input buffer length doesn't match node frame size
+    SizeMismatch { expected: usize, got: usize },
+}
+
+impl fmt::Display for DspError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DspError::SizeMismatch { expected, got } =>
+                write!(f, "Buffer size mismatch: expected {}, got {}", expected, got),
+        }
+    }
+}
+
+impl std::error::Error for DspError {}
+
+/// Check if buffer has the frame size expected by the node
+pub(crate) fn check_size(expected: usize, got: usize) -> Result<(), DspError> {
+    if expected == got {
+        Ok(())
+    } else {
+        Err(DspError::SizeMismatch { expected, got })
+    }
+}
+
 /// Node which produces signal. E.g generators
 pub trait SourceNode {
     type Buffer;
@@ -51,6 +93,12 @@ impl RealToComplexNode {
     pub fn new(size: usize) -> RealToComplexNode {
         RealToComplexNode {output: vec![Complex32::new(0.0, 0.0); size]}
     }
+
+    /// Same as process, but returns error if input size doesn't match node size
+    pub fn try_process(&mut self, input: &RealBuffer) -> Result<&ComplexBuffer, DspError> {
+        check_size(self.output.len(), input.len())?;
+        Ok(self.process(input))
+    }
 }
 
 impl ProcessingNode for RealToComplexNode {
@@ -66,14 +114,36 @@ impl ProcessingNode for RealToComplexNode {
     }
 }
 
+/// Which value is extracted from a complex number
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ComplexToRealMode {
+    Real,
+    Imag,
+    Magnitude,
+    Phase,
+}
+
 /// Convert Complex buffer into Real
 pub struct ComplexToRealNode {
+    mode: ComplexToRealMode,
     output: RealBuffer,
 }
 
 impl ComplexToRealNode {
+    /// Node which takes the real part
     pub fn new(size: usize) -> ComplexToRealNode {
-        ComplexToRealNode {output: vec![0.0; size]}
+        ComplexToRealNode::with_mode(size, ComplexToRealMode::Real)
+    }
+
+    /// Node which extracts the value selected by mode
+    pub fn with_mode(size: usize, mode: ComplexToRealMode) -> ComplexToRealNode {
+        ComplexToRealNode {mode, output: vec![0.0; size]}
+    }
+
+    /// Same as process, but returns error if input size doesn't match node size
+    pub fn try_process(&mut self, input: &ComplexBuffer) -> Result<&RealBuffer, DspError> {
+        check_size(self.output.len(), input.len())?;
+        Ok(self.process(input))
     }
 }
 
@@ -84,18 +154,131 @@ impl ProcessingNode for ComplexToRealNode {
     fn process(&mut self, input: &ComplexBuffer) -> &RealBuffer {
         let n = usize::min(input.len(), self.output.len());
         for i in 0..n {
-            self.output[i] = input[i].re;
+            self.output[i] = match self.mode {
+                ComplexToRealMode::Real => input[i].re,
+                ComplexToRealMode::Imag => input[i].im,
+                ComplexToRealMode::Magnitude => input[i].norm(),
+                ComplexToRealMode::Phase => input[i].arg(),
+            };
         }
         &self.output
     }
 }
 
+/// Chain of real to real processing nodes.
+/// Output of each node is an input to the next one.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::{Pipeline, ProcessingNode};
+/// use dsp::signals::GainNode;
+/// use dsp::filters::FirNode;
+/// 
+/// let mut pipeline = Pipeline::new()
+///     .then(GainNode::new(2.0, 3))
+///     .then(FirNode::new(vec![0.5, 0.5], 3));
+/// assert_eq!(pipeline.process(&vec![1.0, 2.0, 3.0]), &vec![1.0, 3.0, 5.0]);
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    nodes: Vec<Box<dyn ProcessingNode<InBuffer = RealBuffer, OutBuffer = RealBuffer>>>,
+    // Used only when there are no nodes
+    output: RealBuffer,
+}
+
+impl Pipeline {
+    /// Create empty pipeline. It copies input to output
+    pub fn new() -> Pipeline {
+        Pipeline { nodes: vec![], output: vec![] }
+    }
+
+    /// Append node at the end of the pipeline
+    pub fn then<N>(mut self, node: N) -> Pipeline 
+        where N: ProcessingNode<InBuffer = RealBuffer, OutBuffer = RealBuffer> + 'static
+    {
+        self.nodes.push(Box::new(node));
+        self
+    }
+
+    /// Number of nodes in the pipeline
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl ProcessingNode for Pipeline {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        let mut nodes = self.nodes.iter_mut();
+        match nodes.next() {
+            Some(first) => nodes.fold(first.process(input), |buffer, node| node.process(buffer)),
+            None => {
+                self.output.clone_from(input);
+                &self.output
+            }
+        }
+    }
+}
+
+/// Sink which records all consumed frames into a single buffer
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::{SinkNode, VecSink};
+/// 
+/// let mut sink = VecSink::new();
+/// sink.consume(&vec![1.0, 2.0]);
+/// sink.consume(&vec![3.0]);
+/// assert_eq!(sink.take(), vec![1.0, 2.0, 3.0]);
+/// assert!(sink.data().is_empty());
+/// ```
+#[derive(Default)]
+pub struct VecSink {
+    data: RealBuffer,
+}
+
+impl VecSink {
+    pub fn new() -> VecSink {
+        VecSink { data: vec![] }
+    }
+
+    /// Data recorded so far
+    pub fn data(&self) -> &RealBuffer {
+        &self.data
+    }
+
+    /// Return recorded data and clear the sink
+    pub fn take(&mut self) -> RealBuffer {
+        std::mem::take(&mut self.data)
+    }
+}
+
+impl SinkNode for VecSink {
+    type Buffer = RealBuffer;
+
+    fn consume(&mut self, input: &RealBuffer) {
+        self.data.extend_from_slice(input);
+    }
+}
+
 /// ------------------------------------------------------------------------------------------------
 /// Module unit tests
 /// ------------------------------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
     use crate::num_complex::Complex32;
+    use crate::generators::{GenNode, SineGen, WhiteNoiseGen};
+    use crate::signals::GainNode;
+    use crate::windows::{hann, WindowNode};
+    use crate::filters::FirNode;
     use super::*;
 
     #[test]
@@ -122,4 +305,76 @@ mod tests {
         assert_eq!(real, &expected);
     }
 
+    #[test]
+    fn test_complex_to_real_modes() {
+        let complex = vec![Complex32::new(3.0, 4.0), Complex32::new(0.0, -2.0)];
+        let mut ctr = ComplexToRealNode::with_mode(2, ComplexToRealMode::Real);
+        assert_eq!(ctr.process(&complex), &vec![3.0, 0.0]);
+        let mut ctr = ComplexToRealNode::with_mode(2, ComplexToRealMode::Imag);
+        assert_eq!(ctr.process(&complex), &vec![4.0, -2.0]);
+        let mut ctr = ComplexToRealNode::with_mode(2, ComplexToRealMode::Magnitude);
+        assert_eq!(ctr.process(&complex), &vec![5.0, 2.0]);
+        let mut ctr = ComplexToRealNode::with_mode(2, ComplexToRealMode::Phase);
+        let phase = ctr.process(&complex);
+        assert!((phase[0] - 0.927_295_2).abs() < 1e-6);
+        assert!((phase[1] + std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_try_process() {
+        let mut rtc = RealToComplexNode::new(2);
+        assert_eq!(rtc.try_process(&vec![1.0, 2.0]).unwrap(), 
+                   &vec![Complex32::new(1.0, 0.0), Complex32::new(2.0, 0.0)]);
+        assert_eq!(rtc.try_process(&vec![1.0]), Err(DspError::SizeMismatch { expected: 2, got: 1 }));
+        let mut ctr = ComplexToRealNode::new(2);
+        assert_eq!(ctr.try_process(&vec![Complex32::new(1.0, 0.0); 3]),
+                   Err(DspError::SizeMismatch { expected: 2, got: 3 }));
+    }
+
+    #[test]
+    fn test_vec_sink() {
+        let mut gen = GenNode::new(Box::new(SineGen::new(1.0)), 8.0, 4);
+        let mut sink = VecSink::new();
+        let mut expected = vec![];
+        for _ in 0..3 {
+            let frame = gen.next_frame();
+            expected.extend_from_slice(frame);
+            sink.consume(frame);
+        }
+        assert_eq!(sink.data().len(), 12);
+        assert_eq!(sink.take(), expected);
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(1)), 16.0, 16);
+        let mut pipeline = Pipeline::new()
+            .then(GainNode::new(0.5, 16))
+            .then(WindowNode::new(hann(16, 0, 16)))
+            .then(FirNode::new(vec![0.25, 0.5, 0.25], 16));
+        assert_eq!(pipeline.len(), 3);
+        let mut gain = GainNode::new(0.5, 16);
+        let mut window = WindowNode::new(hann(16, 0, 16));
+        let mut fir = FirNode::new(vec![0.25, 0.5, 0.25], 16);
+        for _ in 0..3 {
+            let signal = gen.next_frame();
+            let expected = fir.process(window.process(gain.process(signal)));
+            assert_eq!(pipeline.process(signal), expected);
+        }
+    }
+
+    #[test]
+    fn test_empty_pipeline() {
+        let mut pipeline = Pipeline::new();
+        assert!(pipeline.is_empty());
+        assert_eq!(pipeline.process(&vec![1.0, 2.0]), &vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_error_display() {
+        let err = DspError::SizeMismatch { expected: 4, got: 3 };
+        assert_eq!(err.to_string(), "Buffer size mismatch: expected 4, got 3");
+    }
+
 }
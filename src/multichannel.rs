@@ -0,0 +1,66 @@
+//! Multichannel helpers
+//!
+//! Interleaved stereo buffer stores samples as [left, right, left, right, ...]
+//!
+
+use crate::RealBuffer;
+
+
+/// Interleave two channels into stereo buffer.
+/// If channels have different lengths, only the common length is used.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::multichannel::interleave;
+/// 
+/// assert_eq!(interleave(&vec![1.0, 2.0], &vec![-1.0, -2.0]), vec![1.0, -1.0, 2.0, -2.0]);
+/// ```
+pub fn interleave(left: &RealBuffer, right: &RealBuffer) -> RealBuffer {
+    let mut stereo = Vec::with_capacity(2 * usize::min(left.len(), right.len()));
+    for (&l, &r) in left.iter().zip(right) {
+        stereo.push(l);
+        stereo.push(r);
+    }
+    stereo
+}
+
+/// Split stereo buffer into (left, right) channels.
+/// If the buffer has odd length, the last incomplete sample is dropped.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::multichannel::deinterleave;
+/// 
+/// let (left, right) = deinterleave(&vec![1.0, -1.0, 2.0, -2.0, 3.0]);
+/// assert_eq!(left, vec![1.0, 2.0]);
+/// assert_eq!(right, vec![-1.0, -2.0]);
+/// ```
+pub fn deinterleave(stereo: &RealBuffer) -> (RealBuffer, RealBuffer) {
+    stereo.chunks_exact(2).map(|frame| (frame[0], frame[1])).unzip()
+}
+
+
+/// ------------------------------------------------------------------------------------------------
+/// Module unit tests
+/// ------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let left = vec![0.1, 0.2, 0.3, 0.4];
+        let right = vec![-0.5, 0.0, 0.5, 1.0];
+        let stereo = interleave(&left, &right);
+        assert_eq!(stereo, vec![0.1, -0.5, 0.2, 0.0, 0.3, 0.5, 0.4, 1.0]);
+        assert_eq!(deinterleave(&stereo), (left, right));
+    }
+
+    #[test]
+    fn test_uneven_lengths() {
+        assert_eq!(interleave(&vec![1.0, 2.0, 3.0], &vec![4.0]), vec![1.0, 4.0]);
+        assert_eq!(deinterleave(&vec![1.0]), (vec![], vec![]));
+    }
+}
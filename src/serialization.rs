@@ -0,0 +1,105 @@
+//! Serialization of signal buffers with serde
+//!
+//! Available with the `serde` feature.
+//! Complex numbers are serialized as [re, im] pairs.
+//!
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::num_complex::Complex32;
+use crate::{ComplexBuffer, RealBuffer};
+
+
+/// Serializable wrapper for RealBuffer
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::serialization::SerReal;
+/// 
+/// let json = serde_json::to_string(&SerReal(vec![1.0, 0.5])).unwrap();
+/// assert_eq!(json, "[1.0,0.5]");
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SerReal(pub RealBuffer);
+
+/// Serializable wrapper for ComplexBuffer
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::num_complex::Complex32;
+/// use dsp::serialization::SerComplex;
+/// 
+/// let json = serde_json::to_string(&SerComplex(vec![Complex32::new(1.0, -2.0)])).unwrap();
+/// assert_eq!(json, "[[1.0,-2.0]]");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SerComplex(pub ComplexBuffer);
+
+impl Serialize for SerComplex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|c| (c.re, c.im)))
+    }
+}
+
+impl<'de> Deserialize<'de> for SerComplex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SerComplex, D::Error> {
+        let pairs = Vec::<(f32, f32)>::deserialize(deserializer)?;
+        Ok(SerComplex(pairs.into_iter().map(|(re, im)| Complex32::new(re, im)).collect()))
+    }
+}
+
+impl From<RealBuffer> for SerReal {
+    fn from(buffer: RealBuffer) -> SerReal {
+        SerReal(buffer)
+    }
+}
+
+impl From<SerReal> for RealBuffer {
+    fn from(wrapper: SerReal) -> RealBuffer {
+        wrapper.0
+    }
+}
+
+impl From<ComplexBuffer> for SerComplex {
+    fn from(buffer: ComplexBuffer) -> SerComplex {
+        SerComplex(buffer)
+    }
+}
+
+impl From<SerComplex> for ComplexBuffer {
+    fn from(wrapper: SerComplex) -> ComplexBuffer {
+        wrapper.0
+    }
+}
+
+
+/// ------------------------------------------------------------------------------------------------
+/// Module unit tests
+/// ------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complex_round_trip() {
+        let buffer = vec![Complex32::new(0.1, -3.5), Complex32::new(1e-7, 0.0), Complex32::new(-2.0, 1.0 / 3.0)];
+        let json = serde_json::to_string(&SerComplex(buffer.clone())).unwrap();
+        let restored: ComplexBuffer = serde_json::from_str::<SerComplex>(&json).unwrap().into();
+        assert_eq!(restored, buffer);
+    }
+
+    #[test]
+    fn test_real_round_trip() {
+        let buffer = vec![0.1, -1.0 / 3.0, 12345.678];
+        let json = serde_json::to_string(&SerReal::from(buffer.clone())).unwrap();
+        let restored: SerReal = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.0, buffer);
+    }
+
+    #[test]
+    fn test_invalid_complex() {
+        assert!(serde_json::from_str::<SerComplex>("[[1.0]]").is_err());
+    }
+}
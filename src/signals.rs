@@ -1,5 +1,18 @@
 
-use crate::{RealBuffer, ProcessingNode};
+use std::f64::consts::PI as PI64;
+use crate::num_complex::Complex32;
+use crate::{ComplexBuffer, RealBuffer, ProcessingNode, Sample, DspError, check_size};
+use crate::fft::{ForwardFFT, InverseFFT};
+use crate::filters::{FirNode, fir_lowpass};
+
+// Above this length (of the shorter signal) convolution is computed with FFT
+const FFT_CONVOLUTION_THRESHOLD: usize = 64;
+// Length of the anti-aliasing filter per unit of the resampling factor
+const RESAMPLING_TAPS_PER_FACTOR: usize = 16;
+// Half length of the resampling sinc kernel (in samples at the lower rate)
+const RESAMPLING_KERNEL_HALF_WIDTH: f64 = 16.0;
+// Minimum normalized autocorrelation of the pitch peak
+const PITCH_CLARITY_THRESHOLD: f32 = 0.5;
 
 
 /// Change signal amplitude
@@ -7,64 +20,1131 @@ use crate::{RealBuffer, ProcessingNode};
 /// Example
 /// 
 /// ```
-/// use assert_approx_eq::assert_approx_eq;    
-/// use dsp::{ProcessingNode, SourceNode};
-/// use dsp::generators::{SineGen, GenNode};
-/// use dsp::signals::GainNode;
+/// use assert_approx_eq::assert_approx_eq;    
+/// use dsp::{ProcessingNode, SourceNode};
+/// use dsp::generators::{SineGen, GenNode};
+/// use dsp::signals::GainNode;
+/// 
+/// let mut gen = GenNode::new(Box::new(SineGen::new(1.0)), 4.0, 4);
+/// let mut amplitude_node = GainNode::new(2.0, 4);
+/// let signal = gen.next_frame();
+/// let scaled_signal = amplitude_node.process(signal);
+/// assert_approx_eq!(scaled_signal[0], 0.0, 1e-5f32);
+/// assert_approx_eq!(scaled_signal[1], 2.0, 1e-5f32);
+/// assert_approx_eq!(scaled_signal[2], 0.0, 1e-5f32);
+/// assert_approx_eq!(scaled_signal[3], -2.0, 1e-5f32);
+/// ```
+pub struct GainNode<T: Sample = f32> {
+    scale: T,
+    output: Vec<T>,
+}
+
+impl<T: Sample> GainNode<T> {
+    pub fn new(scale: T, frame_size: usize) -> GainNode<T> {
+        GainNode { scale, output: vec![T::zero(); frame_size] }
+    }
+
+    /// Same as process, but returns error if input size doesn't match frame size
+    pub fn try_process(&mut self, input: &Vec<T>) -> Result<&Vec<T>, DspError> {
+        check_size(self.output.len(), input.len())?;
+        Ok(self.process(input))
+    }
+}
+
+impl<T: Sample> ProcessingNode for GainNode<T> {
+    type InBuffer = Vec<T>;
+    type OutBuffer = Vec<T>;
+    
+    fn process(&mut self, input: &Self::InBuffer) -> &Vec<T> {
+        let n = usize::min(input.len(), self.output.len());
+        for i in 0..n {
+            self.output[i] = self.scale * input[i];
+        }
+        &self.output
+    }
+
+}
+
+
+/// Sum several signals
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::SumNode;
+/// 
+/// let mut sum_node = SumNode::new(2);
+/// let a = vec![1.0, 2.0];
+/// let b = vec![0.5, 0.5];
+/// assert_eq!(sum_node.process(&a, &b), &vec![1.5, 2.5]);
+/// assert_eq!(sum_node.process_many(&[&a, &b, &a]), &vec![2.5, 4.5]);
+/// ```
+pub struct SumNode {
+    output: RealBuffer,
+}
+
+impl SumNode {
+    pub fn new(frame_size: usize) -> SumNode {
+        SumNode { output: vec![0.0; frame_size] }
+    }
+    
+    pub fn process(&mut self, input1: &RealBuffer, input2: &RealBuffer) -> &RealBuffer {
+        let n = usize::min(usize::min(input1.len(), input2.len()), self.output.len());
+        for i in 0..n {
+            self.output[i] = input1[i] + input2[i];
+        }
+        &self.output
+    }
+
+    /// Same as process, but returns error if any input size doesn't match frame size
+    pub fn try_process(&mut self, input1: &RealBuffer, input2: &RealBuffer) -> Result<&RealBuffer, DspError> {
+        check_size(self.output.len(), input1.len())?;
+        check_size(self.output.len(), input2.len())?;
+        Ok(self.process(input1, input2))
+    }
+
+    /// Sum any number of signals. Only the common length of all inputs is summed.
+    pub fn process_many(&mut self, inputs: &[&RealBuffer]) -> &RealBuffer {
+        let n = inputs.iter().fold(self.output.len(), |acc, input| usize::min(acc, input.len()));
+        for i in 0..n {
+            self.output[i] = inputs.iter().map(|input| input[i]).sum();
+        }
+        &self.output
+    }
+
+}
+
+
+/// Subtract two signals
+/// output = input1 - input2
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::DiffNode;
+/// 
+/// let mut diff_node = DiffNode::new(2);
+/// assert_eq!(diff_node.process(&vec![1.0, 2.0], &vec![0.5, 3.0]), &vec![0.5, -1.0]);
+/// ```
+pub struct DiffNode {
+    output: RealBuffer,
+}
+
+impl DiffNode {
+    pub fn new(frame_size: usize) -> DiffNode {
+        DiffNode { output: vec![0.0; frame_size] }
+    }
+    
+    pub fn process(&mut self, input1: &RealBuffer, input2: &RealBuffer) -> &RealBuffer {
+        let n = usize::min(usize::min(input1.len(), input2.len()), self.output.len());
+        for i in 0..n {
+            self.output[i] = input1[i] - input2[i];
+        }
+        &self.output
+    }
+
+}
+
+
+/// Multiply two signals (ring modulation)
+/// output = input1 * input2
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::MultiplyNode;
+/// 
+/// let mut multiply_node = MultiplyNode::new(2);
+/// assert_eq!(multiply_node.process(&vec![1.0, 2.0], &vec![0.5, -3.0]), &vec![0.5, -6.0]);
+/// ```
+pub struct MultiplyNode {
+    output: RealBuffer,
+}
+
+impl MultiplyNode {
+    pub fn new(frame_size: usize) -> MultiplyNode {
+        MultiplyNode { output: vec![0.0; frame_size] }
+    }
+    
+    pub fn process(&mut self, input1: &RealBuffer, input2: &RealBuffer) -> &RealBuffer {
+        let n = usize::min(usize::min(input1.len(), input2.len()), self.output.len());
+        for i in 0..n {
+            self.output[i] = input1[i] * input2[i];
+        }
+        &self.output
+    }
+
+}
+
+
+/// Mix signals with individual gains
+/// output = sum(gains[k] * inputs[k])
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::MixNode;
+/// 
+/// let mut mix_node = MixNode::new(vec![0.5, 2.0], 2);
+/// assert_eq!(mix_node.process(&[&vec![1.0, 2.0], &vec![1.0, -1.0]]), &vec![2.5, -1.0]);
+/// ```
+pub struct MixNode {
+    gains: Vec<f32>,
+    output: RealBuffer,
+}
+
+impl MixNode {
+    pub fn new(gains: Vec<f32>, frame_size: usize) -> MixNode {
+        MixNode { gains, output: vec![0.0; frame_size] }
+    }
+    
+    /// Weighted sum of the inputs. Panics if the number of inputs doesn't match the number of gains
+    pub fn process(&mut self, inputs: &[&RealBuffer]) -> &RealBuffer {
+        assert_eq!(inputs.len(), self.gains.len(), "Number of inputs must match number of gains");
+        let n = inputs.iter().fold(self.output.len(), |acc, input| usize::min(acc, input.len()));
+        for i in 0..n {
+            self.output[i] = inputs.iter().zip(&self.gains).map(|(input, g)| g * input[i]).sum();
+        }
+        &self.output
+    }
+
+}
+
+
+/// Amplitude modulation
+/// output = carrier * (1 + depth * modulator)
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::ModulateAmNode;
+/// 
+/// let mut am_node = ModulateAmNode::new(0.5, 3);
+/// let carrier = vec![1.0, -1.0, 1.0];
+/// let modulator = vec![1.0, 0.0, -1.0];
+/// assert_eq!(am_node.process(&carrier, &modulator), &vec![1.5, -1.0, 0.5]);
+/// ```
+pub struct ModulateAmNode {
+    depth: f32,
+    output: RealBuffer,
+}
+
+impl ModulateAmNode {
+    pub fn new(depth: f32, frame_size: usize) -> ModulateAmNode {
+        ModulateAmNode { depth, output: vec![0.0; frame_size] }
+    }
+    
+    pub fn process(&mut self, carrier: &RealBuffer, modulator: &RealBuffer) -> &RealBuffer {
+        let n = usize::min(usize::min(carrier.len(), modulator.len()), self.output.len());
+        for i in 0..n {
+            self.output[i] = carrier[i] * (1.0 + self.depth * modulator[i]);
+        }
+        &self.output
+    }
+
+}
+
+/// Hard clipping. Each sample is limited to the range [lower, upper]
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::signals::ClipNode;
+/// 
+/// let mut clip_node = ClipNode::new(0.5, 3);
+/// assert_eq!(clip_node.process(&vec![0.2, 0.7, -1.0]), &vec![0.2, 0.5, -0.5]);
+/// let mut clip_node = ClipNode::with_bounds(-0.1, 0.5, 3);
+/// assert_eq!(clip_node.process(&vec![0.2, 0.7, -1.0]), &vec![0.2, 0.5, -0.1]);
+/// ```
+pub struct ClipNode {
+    lower: f32,
+    upper: f32,
+    output: RealBuffer,
+}
+
+impl ClipNode {
+    /// Symmetric clipping to [-threshold, threshold]
+    pub fn new(threshold: f32, frame_size: usize) -> ClipNode {
+        let threshold = threshold.abs();
+        ClipNode::with_bounds(-threshold, threshold, frame_size)
+    }
+
+    /// Asymmetric clipping to [lower, upper]
+    pub fn with_bounds(lower: f32, upper: f32, frame_size: usize) -> ClipNode {
+        assert!(lower <= upper, "Lower bound can't be greater than upper bound");
+        ClipNode { lower, upper, output: vec![0.0; frame_size] }
+    }
+}
+
+impl ProcessingNode for ClipNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, x) in self.output.iter_mut().zip(input) {
+            *y = x.clamp(self.lower, self.upper);
+        }
+        &self.output
+    }
+}
+
+
+/// Soft clipping (saturation)
+/// output = tanh(drive * input) / norm
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::ProcessingNode;
+/// use dsp::signals::SoftClipNode;
+/// 
+/// let mut clip_node = SoftClipNode::normalized(2.0, 2);
+/// let output = clip_node.process(&vec![1.0, -1.0]);
+/// assert_approx_eq!(output[0], 1.0, 1e-6);
+/// assert_approx_eq!(output[1], -1.0, 1e-6);
+/// ```
+pub struct SoftClipNode {
+    drive: f32,
+    norm: f32,
+    output: RealBuffer,
+}
+
+impl SoftClipNode {
+    /// Output tanh(drive * input). Large inputs approach ±1
+    pub fn new(drive: f32, frame_size: usize) -> SoftClipNode {
+        SoftClipNode { drive, norm: 1.0, output: vec![0.0; frame_size] }
+    }
+
+    /// Output scaled by 1/tanh(drive), so input ±1 gives output ±1
+    pub fn normalized(drive: f32, frame_size: usize) -> SoftClipNode {
+        SoftClipNode { drive, norm: drive.tanh(), output: vec![0.0; frame_size] }
+    }
+}
+
+impl ProcessingNode for SoftClipNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, x) in self.output.iter_mut().zip(input) {
+            *y = (self.drive * x).tanh() / self.norm;
+        }
+        &self.output
+    }
+}
+
+
+/// Envelope follower.
+/// Tracks rectified input with exponential attack and release (time to reach 1-1/e of the step).
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::signals::EnvelopeFollowerNode;
+/// 
+/// let mut follower = EnvelopeFollowerNode::new(0.0, 1.0, 10.0, 3);
+/// let output = follower.process(&vec![-1.0, 0.0, 0.0]);
+/// assert_eq!(output[0], 1.0);
+/// assert!(output[1] > 0.9 && output[2] < output[1]);
+/// ```
+pub struct EnvelopeFollowerNode {
+    attack_coef: f32,
+    release_coef: f32,
+    envelope: f32,
+    output: RealBuffer,
+}
+
+impl EnvelopeFollowerNode {
+    /// Create envelope follower
+    ///   * attack_s - Attack time in seconds
+    ///   * release_s - Release time in seconds
+    ///   * sample_rate - Sampling frequency
+    ///   * frame_size - Size of the output buffer
+    pub fn new(attack_s: f32, release_s: f32, sample_rate: f32, frame_size: usize) -> EnvelopeFollowerNode {
+        EnvelopeFollowerNode {
+            attack_coef: time_constant_coef(attack_s, sample_rate),
+            release_coef: time_constant_coef(release_s, sample_rate),
+            envelope: 0.0,
+            output: vec![0.0; frame_size],
+        }
+    }
+}
+
+impl ProcessingNode for EnvelopeFollowerNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, x) in self.output.iter_mut().zip(input) {
+            let level = x.abs();
+            let coef = if level > self.envelope { self.attack_coef } else { self.release_coef };
+            self.envelope = coef * self.envelope + (1.0 - coef) * level;
+            *y = self.envelope;
+        }
+        &self.output
+    }
+}
+
+/// Dynamic range compressor.
+/// Input level is tracked by the envelope follower with the given attack and release times.
+/// When the envelope is above the threshold, the level over it is divided by ratio.
+/// Makeup gain is applied after the compression.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::signals::CompressorNode;
+/// 
+/// let mut compressor = CompressorNode::new(-20.0, 4.0, 0.0, 0.1, 44100.0, 4);
+/// // 0 dBFS is 20 dB over the threshold, so the output is at -15 dBFS
+/// let output = compressor.process(&vec![1.0; 4]);
+/// assert!((20.0 * output[3].log10() + 15.0).abs() < 1e-3);
+/// ```
+pub struct CompressorNode {
+    threshold_db: f32,
+    ratio: f32,
+    makeup_db: f32,
+    attack_coef: f32,
+    release_coef: f32,
+    envelope: f32,
+    output: RealBuffer,
+}
+
+impl CompressorNode {
+    /// Create compressor without makeup gain
+    ///   * threshold_db - Level in dBFS above which the signal is compressed
+    ///   * ratio - Compression ratio. 1 means no compression, large values approach limiting
+    ///   * attack_s - Attack time of the level detector in seconds
+    ///   * release_s - Release time of the level detector in seconds
+    ///   * sample_rate - Sampling frequency
+    ///   * frame_size - Size of the output buffer
+    pub fn new(threshold_db: f32, ratio: f32, attack_s: f32, release_s: f32,
+               sample_rate: f32, frame_size: usize) -> CompressorNode {
+        assert!(ratio >= 1.0, "Ratio must be at least 1");
+        CompressorNode {
+            threshold_db,
+            ratio,
+            makeup_db: 0.0,
+            attack_coef: time_constant_coef(attack_s, sample_rate),
+            release_coef: time_constant_coef(release_s, sample_rate),
+            envelope: 0.0,
+            output: vec![0.0; frame_size],
+        }
+    }
+
+    /// Set gain in dB applied to the compressed signal
+    pub fn set_makeup_gain(&mut self, makeup_db: f32) {
+        self.makeup_db = makeup_db;
+    }
+}
+
+impl ProcessingNode for CompressorNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, x) in self.output.iter_mut().zip(input) {
+            let level = x.abs();
+            let coef = if level > self.envelope { self.attack_coef } else { self.release_coef };
+            self.envelope = coef * self.envelope + (1.0 - coef) * level;
+            let over_db = 20.0 * self.envelope.log10() - self.threshold_db;
+            let reduction_db = if over_db > 0.0 { over_db * (1.0 - 1.0 / self.ratio) } else { 0.0 };
+            *y = x * 10f32.powf((self.makeup_db - reduction_db) / 20.0);
+        }
+        &self.output
+    }
+}
+
+/// Noise gate.
+/// Input level is tracked by the peak envelope follower with the attack and release times.
+/// Gate opens when the envelope exceeds the threshold and stays open for the hold time
+/// after the envelope falls below it. Then it closes with the release time.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::signals::GateNode;
+/// 
+/// let mut gate = GateNode::new(-20.0, 0.0, 0.0, 0.0, 44100.0, 4);
+/// assert_eq!(gate.process(&vec![0.5, 0.01, -0.5, 0.01]), &vec![0.5, 0.0, -0.5, 0.0]);
+/// ```
+pub struct GateNode {
+    threshold: f32,
+    attack_coef: f32,
+    release_coef: f32,
+    hold_samples: usize,
+    hold_counter: usize,
+    envelope: f32,
+    gain: f32,
+    output: RealBuffer,
+}
+
+impl GateNode {
+    /// Create noise gate
+    ///   * threshold_db - Level in dBFS above which the gate opens
+    ///   * attack_s - Time constant of opening in seconds
+    ///   * hold_s - Time in seconds the gate stays open after the envelope falls below threshold
+    ///   * release_s - Time constant of closing in seconds
+    ///   * sample_rate - Sampling frequency
+    ///   * frame_size - Size of the output buffer
+    pub fn new(threshold_db: f32, attack_s: f32, hold_s: f32, release_s: f32,
+               sample_rate: f32, frame_size: usize) -> GateNode {
+        GateNode {
+            threshold: 10f32.powf(threshold_db / 20.0),
+            attack_coef: time_constant_coef(attack_s, sample_rate),
+            release_coef: time_constant_coef(release_s, sample_rate),
+            hold_samples: (hold_s * sample_rate).round() as usize,
+            hold_counter: 0,
+            envelope: 0.0,
+            gain: 0.0,
+            output: vec![0.0; frame_size],
+        }
+    }
+}
+
+impl ProcessingNode for GateNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, x) in self.output.iter_mut().zip(input) {
+            let level = x.abs();
+            let coef = if level > self.envelope { self.attack_coef } else { self.release_coef };
+            self.envelope = coef * self.envelope + (1.0 - coef) * level;
+            let open = if self.envelope > self.threshold {
+                self.hold_counter = self.hold_samples;
+                true
+            } else if self.hold_counter > 0 {
+                self.hold_counter -= 1;
+                true
+            } else {
+                false
+            };
+            self.gain = if open {
+                self.attack_coef * self.gain + (1.0 - self.attack_coef)
+            } else {
+                self.release_coef * self.gain
+            };
+            *y = x * self.gain;
+        }
+        &self.output
+    }
+}
+
+// One pole smoothing coefficient for the given time constant
+fn time_constant_coef(time_s: f32, sample_rate: f32) -> f32 {
+    if time_s > 0.0 {
+        (-1.0 / (time_s * sample_rate)).exp()
+    } else {
+        0.0
+    }
+}
+
+
+/// Delay line with fractional delay (linear interpolation)
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::signals::DelayNode;
+/// 
+/// let mut delay = DelayNode::new(1.5, 4, 4);
+/// assert_eq!(delay.process(&vec![2.0, 4.0, 0.0, 0.0]), &vec![0.0, 1.0, 3.0, 2.0]);
+/// ```
+pub struct DelayNode {
+    delay: f32,
+    max_delay: usize,
+    // Circular buffer with past samples. pos points to the most recent one
+    buffer: RealBuffer,
+    pos: usize,
+    output: RealBuffer,
+}
+
+impl DelayNode {
+    /// Create delay line
+    ///   * delay_samples - Delay in samples. Can be fractional
+    ///   * max_delay - Maximum delay supported by this node
+    ///   * frame_size - Size of the output buffer
+    pub fn new(delay_samples: f32, max_delay: usize, frame_size: usize) -> DelayNode {
+        let mut node = DelayNode {
+            delay: 0.0,
+            max_delay,
+            buffer: vec![0.0; max_delay + 2],
+            pos: 0,
+            output: vec![0.0; frame_size],
+        };
+        node.set_delay(delay_samples);
+        node
+    }
+
+    /// Change delay. The value is limited to range [0, max_delay]
+    pub fn set_delay(&mut self, delay_samples: f32) {
+        self.delay = delay_samples.clamp(0.0, self.max_delay as f32);
+    }
+}
+
+impl ProcessingNode for DelayNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        let size = self.buffer.len();
+        let whole = self.delay.floor() as usize;
+        let frac = self.delay - whole as f32;
+        for (y, &x) in self.output.iter_mut().zip(input) {
+            self.pos = (self.pos + 1) % size;
+            self.buffer[self.pos] = x;
+            let a = self.buffer[(self.pos + size - whole) % size];
+            let b = self.buffer[(self.pos + size - whole - 1) % size];
+            *y = (1.0 - frac) * a + frac * b;
+        }
+        &self.output
+    }
+}
+
+
+/// Decimation. Lowpass filters the signal below the new Nyquist frequency
+/// and keeps every factor-th sample.
+/// The output frame is factor times shorter than the input frame.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::signals::DecimateNode;
+/// 
+/// let mut decimate = DecimateNode::new(4, 256);
+/// let output = decimate.process(&vec![1.0; 256]);
+/// assert_eq!(output.len(), 64);
+/// assert!((output[63] - 1.0).abs() < 1e-5);
+/// ```
+pub struct DecimateNode {
+    factor: usize,
+    filter: FirNode,
+    output: RealBuffer,
+}
+
+impl DecimateNode {
+    /// Create decimation node
+    ///   * factor - Downsampling factor
+    ///   * frame_size - Input frame size. Must be a multiple of factor
+    pub fn new(factor: usize, frame_size: usize) -> DecimateNode {
+        assert!(factor > 0, "Factor must be greater than 0");
+        assert_eq!(frame_size % factor, 0, "Frame size must be a multiple of the factor");
+        // Cutoff is given relative to sample rate 1
+        let taps = fir_lowpass(RESAMPLING_TAPS_PER_FACTOR * factor + 1, 0.5 / factor as f32, 1.0);
+        DecimateNode {
+            factor,
+            filter: FirNode::new(taps, frame_size),
+            output: vec![0.0; frame_size / factor],
+        }
+    }
+}
+
+impl ProcessingNode for DecimateNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        let filtered = self.filter.process(input);
+        for (y, x) in self.output.iter_mut().zip(filtered.iter().step_by(self.factor)) {
+            *y = *x;
+        }
+        &self.output
+    }
+}
+
+
+/// Interpolation. Inserts factor-1 zeros between samples and removes
+/// the spectrum images with lowpass filter.
+/// The output frame is factor times longer than the input frame.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::signals::InterpolateNode;
+/// 
+/// let mut interpolate = InterpolateNode::new(2, 64);
+/// let output = interpolate.process(&vec![1.0; 64]);
+/// assert_eq!(output.len(), 128);
+/// assert!((output[127] - 1.0).abs() < 1e-2);
+/// ```
+pub struct InterpolateNode {
+    factor: usize,
+    filter: FirNode,
+    upsampled: RealBuffer,
+}
+
+impl InterpolateNode {
+    /// Create interpolation node
+    ///   * factor - Upsampling factor
+    ///   * frame_size - Input frame size
+    pub fn new(factor: usize, frame_size: usize) -> InterpolateNode {
+        assert!(factor > 0, "Factor must be greater than 0");
+        // Gain compensates for energy lost by inserting zeros
+        let taps = fir_lowpass(RESAMPLING_TAPS_PER_FACTOR * factor + 1, 0.5 / factor as f32, 1.0)
+            .iter()
+            .map(|t| t * factor as f32)
+            .collect();
+        InterpolateNode {
+            factor,
+            filter: FirNode::new(taps, frame_size * factor),
+            upsampled: vec![0.0; frame_size * factor],
+        }
+    }
+}
+
+impl ProcessingNode for InterpolateNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (frame, x) in self.upsampled.chunks_mut(self.factor).zip(input) {
+            frame[0] = *x;
+        }
+        self.filter.process(&self.upsampled)
+    }
+}
+
+
+/// Discrete differentiator
+/// y[n] = (x[n] - x[n-1]) * sample_rate
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::signals::DifferentiatorNode;
+/// 
+/// let mut diff = DifferentiatorNode::new(2.0, 3);
+/// assert_eq!(diff.process(&vec![1.0, 2.0, 4.0]), &vec![2.0, 2.0, 4.0]);
+/// ```
+pub struct DifferentiatorNode {
+    sample_rate: f32,
+    last_input: f32,
+    output: RealBuffer,
+}
+
+impl DifferentiatorNode {
+    pub fn new(sample_rate: f32, frame_size: usize) -> DifferentiatorNode {
+        DifferentiatorNode { sample_rate, last_input: 0.0, output: vec![0.0; frame_size] }
+    }
+}
+
+impl ProcessingNode for DifferentiatorNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, &x) in self.output.iter_mut().zip(input) {
+            *y = (x - self.last_input) * self.sample_rate;
+            self.last_input = x;
+        }
+        &self.output
+    }
+}
+
+
+/// Running integrator
+/// y[n] = leak * y[n-1] + x[n] / sample_rate
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::signals::IntegratorNode;
+/// 
+/// let mut integrator = IntegratorNode::new(2.0, 3);
+/// assert_eq!(integrator.process(&vec![1.0, 1.0, 2.0]), &vec![0.5, 1.0, 2.0]);
+/// ```
+pub struct IntegratorNode {
+    sample_rate: f32,
+    leak: f32,
+    sum: f32,
+    output: RealBuffer,
+}
+
+impl IntegratorNode {
+    /// Ideal integrator (leak = 1)
+    pub fn new(sample_rate: f32, frame_size: usize) -> IntegratorNode {
+        IntegratorNode::with_leak(sample_rate, 1.0, frame_size)
+    }
+
+    /// Leaky integrator. Leak slightly below 1 (e.g. 0.999) prevents drift caused by DC offset
+    pub fn with_leak(sample_rate: f32, leak: f32, frame_size: usize) -> IntegratorNode {
+        IntegratorNode { sample_rate, leak, sum: 0.0, output: vec![0.0; frame_size] }
+    }
+}
+
+impl ProcessingNode for IntegratorNode {
+    type InBuffer = RealBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
+        for (y, &x) in self.output.iter_mut().zip(input) {
+            self.sum = self.leak * self.sum + x / self.sample_rate;
+            *y = self.sum;
+        }
+        &self.output
+    }
+}
+
+
+
+/// Full linear convolution of two signals. The output has a.len() + b.len() - 1 samples.
+/// Long signals are convolved with FFT.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::convolve;
+/// 
+/// assert_eq!(convolve(&vec![1.0, 2.0, 3.0], &vec![0.0, 1.0, 0.5]), vec![0.0, 1.0, 2.5, 4.0, 1.5]);
+/// ```
+pub fn convolve(a: &RealBuffer, b: &RealBuffer) -> RealBuffer {
+    if usize::min(a.len(), b.len()) > FFT_CONVOLUTION_THRESHOLD {
+        convolve_fft(a, b)
+    } else {
+        convolve_direct(a, b)
+    }
+}
+
+/// Linear convolution computed directly from the definition. O(N*M)
+pub fn convolve_direct(a: &RealBuffer, b: &RealBuffer) -> RealBuffer {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut output = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (y, h) in output[i..].iter_mut().zip(b) {
+            *y += x * h;
+        }
+    }
+    output
+}
+
+/// Linear convolution computed with zero padded FFT. O((N+M) log(N+M))
+pub fn convolve_fft(a: &RealBuffer, b: &RealBuffer) -> RealBuffer {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let size = a.len() + b.len() - 1;
+    let fft_size = size.next_power_of_two();
+    let mut spectrum_a = padded_fft(a, fft_size);
+    let spectrum_b = padded_fft(b, fft_size);
+    for (x, y) in spectrum_a.iter_mut().zip(&spectrum_b) {
+        *x *= y;
+    }
+    let mut output = vec![Complex32::new(0.0, 0.0); fft_size];
+    InverseFFT::new(fft_size).process(&mut spectrum_a, &mut output);
+    output.iter().take(size).map(|c| c.re / fft_size as f32).collect()
+}
+
+/// Full cross-correlation r[k] = sum(a[n+k] * b[n]).
+/// The output has a.len() + b.len() - 1 samples. Index i corresponds to lag i - (b.len() - 1).
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::cross_correlate;
 /// 
-/// let mut gen = GenNode::new(Box::new(SineGen::new(1.0)), 4.0, 4);
-/// let mut amplitude_node = GainNode::new(2.0, 4);
-/// let signal = gen.next_frame();
-/// let scaled_signal = amplitude_node.process(signal);
-/// assert_approx_eq!(scaled_signal[0], 0.0, 1e-5f32);
-/// assert_approx_eq!(scaled_signal[1], 2.0, 1e-5f32);
-/// assert_approx_eq!(scaled_signal[2], 0.0, 1e-5f32);
-/// assert_approx_eq!(scaled_signal[3], -2.0, 1e-5f32);
+/// assert_eq!(cross_correlate(&vec![1.0, 2.0, 3.0], &vec![1.0, 1.0]), vec![1.0, 3.0, 5.0, 3.0]);
 /// ```
-pub struct GainNode {
-    scale: f32,
-    output: RealBuffer,
+pub fn cross_correlate(a: &RealBuffer, b: &RealBuffer) -> RealBuffer {
+    let reversed: RealBuffer = b.iter().rev().cloned().collect();
+    convolve(a, &reversed)
+}
+
+/// Lag (in samples) which maximizes cross-correlation.
+/// Positive lag means that signal a is delayed relative to b.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::best_lag;
+/// 
+/// assert_eq!(best_lag(&vec![0.0, 0.0, 1.0, 0.5], &vec![1.0, 0.5, 0.0, 0.0]), 2);
+/// ```
+pub fn best_lag(a: &RealBuffer, b: &RealBuffer) -> i64 {
+    let correlation = cross_correlate(a, b);
+    let index = correlation.iter()
+        .enumerate()
+        .fold((0, f32::NEG_INFINITY), |acc, (i, &v)| if v > acc.1 { (i, v) } else { acc })
+        .0;
+    index as i64 - (b.len() as i64 - 1)
+}
+
+/// Autocorrelation for non negative lags r[k] = sum(x[n+k] * x[n]), k = 0..N-1
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::autocorrelate;
+/// 
+/// assert_eq!(autocorrelate(&vec![1.0, 2.0, 3.0]), vec![14.0, 8.0, 3.0]);
+/// ```
+pub fn autocorrelate(signal: &RealBuffer) -> RealBuffer {
+    let correlation = cross_correlate(signal, signal);
+    correlation.into_iter().skip(signal.len().saturating_sub(1)).collect()
 }
 
-impl GainNode {
-    pub fn new(scale: f32, frame_size: usize) -> GainNode {
-        GainNode { scale, output: vec![0.0; frame_size] }
+/// Estimate fundamental frequency of monophonic signal from its autocorrelation.
+/// Returns the frequency of the first strong autocorrelation peak in range [fmin, fmax]
+/// or None if the signal is not periodic (e.g. noise or silence).
+///   * signal - Analyzed signal. Should contain at least 2 periods of fmin
+///   * sample_rate - Sampling frequency
+///   * fmin, fmax - Searched frequency range
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::SourceNode;
+/// use dsp::generators::{GenNode, SineGen};
+/// use dsp::signals::estimate_pitch;
+/// 
+/// let mut gen = GenNode::new(Box::new(SineGen::new(440.0)), 8000.0, 1024);
+/// let pitch = estimate_pitch(gen.next_frame(), 8000.0, 100.0, 1000.0).unwrap();
+/// assert!((pitch - 440.0).abs() < 1.0);
+/// ```
+pub fn estimate_pitch(signal: &RealBuffer, sample_rate: f32, fmin: f32, fmax: f32) -> Option<f32> {
+    let r = autocorrelate(signal);
+    if r.len() < 3 || r[0] <= 0.0 {
+        return None;
+    }
+    let min_lag = usize::max((sample_rate / fmax).floor() as usize, 1);
+    let max_lag = usize::min((sample_rate / fmin).ceil() as usize, r.len() - 2);
+    if min_lag > max_lag {
+        return None;
     }
+    let is_peak = |k: usize| r[k] > r[k - 1] && r[k] >= r[k + 1];
+    let highest = (min_lag..=max_lag)
+        .filter(|&k| is_peak(k))
+        .fold(0.0, |acc, k| f32::max(acc, r[k] / r[0]));
+    if highest < PITCH_CLARITY_THRESHOLD {
+        return None;
+    }
+    // First peak close to the highest one. This avoids picking multiples of the period
+    let lag = (min_lag..=max_lag).find(|&k| is_peak(k) && r[k] / r[0] >= 0.9 * highest)?;
+    // Parabolic interpolation of the peak position
+    let (left, center, right) = (r[lag - 1], r[lag], r[lag + 1]);
+    let denominator = left - 2.0 * center + right;
+    let delta = if denominator != 0.0 { 0.5 * (left - right) / denominator } else { 0.0 };
+    Some(sample_rate / (lag as f32 + delta))
 }
 
-impl ProcessingNode for GainNode {
-    type InBuffer = RealBuffer;
-    type OutBuffer = RealBuffer;
-    
-    fn process(&mut self, input: &Self::InBuffer) -> &RealBuffer {
-        let n = usize::min(input.len(), self.output.len());
-        for i in 0..n {
-            self.output[i] = self.scale * input[i];
-        }
-        &self.output
+/// Root mean square of the signal. Returns 0 for an empty buffer.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::rms;
+/// 
+/// assert_eq!(rms(&vec![1.0, -1.0, 1.0, -1.0]), 1.0);
+/// ```
+pub fn rms(buf: &RealBuffer) -> f32 {
+    if buf.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = buf.iter().map(|&x| (x as f64) * (x as f64)).sum();
+    (sum / buf.len() as f64).sqrt() as f32
+}
+
+/// Maximum absolute value of the signal. Returns 0 for an empty buffer.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::peak;
+/// 
+/// assert_eq!(peak(&vec![0.5, -0.8, 0.2]), 0.8);
+/// ```
+pub fn peak(buf: &RealBuffer) -> f32 {
+    buf.iter().fold(0.0, |acc, x| f32::max(acc, x.abs()))
+}
+
+/// RMS level in dBFS (full scale is 1.0). Silence gives negative infinity.
+pub fn rms_db(buf: &RealBuffer) -> f32 {
+    20.0 * rms(buf).log10()
+}
+
+/// Peak level in dBFS (full scale is 1.0). Silence gives negative infinity.
+pub fn peak_db(buf: &RealBuffer) -> f32 {
+    20.0 * peak(buf).log10()
+}
+
+/// Scale the signal in place so its peak equals target_peak.
+/// An all-zero signal is left untouched.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::normalize;
+/// 
+/// let mut signal = vec![0.25, -0.5, 0.1];
+/// normalize(&mut signal, 1.0);
+/// assert_eq!(signal, vec![0.5, -1.0, 0.2]);
+/// ```
+pub fn normalize(buf: &mut RealBuffer, target_peak: f32) {
+    let current = peak(buf);
+    if current > 0.0 {
+        let scale = target_peak / current;
+        buf.iter_mut().for_each(|x| *x *= scale);
     }
+}
 
+/// Copy of the signal scaled so its peak equals target_peak
+pub fn normalized(buf: &RealBuffer, target_peak: f32) -> RealBuffer {
+    let mut output = buf.clone();
+    normalize(&mut output, target_peak);
+    output
 }
 
+/// Shape of the fade ramp
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FadeShape {
+    Linear,
+    /// Raised cosine (half of Hann window)
+    Cosine,
+}
 
-/// Sum several signals
-pub struct SumNode {
-    output: RealBuffer,
+/// Apply linear fade in and fade out in place, so the signal starts and ends with silence.
+/// Sample k of the fade in is scaled by k / fade_in_samples, the fade out is its mirror image.
+/// When the fades overlap, both gains are applied to the shared samples.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::apply_fade;
+/// 
+/// let mut signal = vec![1.0; 6];
+/// apply_fade(&mut signal, 2, 4);
+/// assert_eq!(signal, vec![0.0, 0.5, 0.75, 0.5, 0.25, 0.0]);
+/// ```
+pub fn apply_fade(buf: &mut RealBuffer, fade_in_samples: usize, fade_out_samples: usize) {
+    apply_fade_with_shape(buf, fade_in_samples, fade_out_samples, FadeShape::Linear);
 }
 
-impl SumNode {
-    pub fn new(frame_size: usize) -> SumNode {
-        SumNode { output: vec![0.0; frame_size] }
+/// Apply fade in and fade out with the given ramp shape. See apply_fade.
+pub fn apply_fade_with_shape(buf: &mut RealBuffer, fade_in_samples: usize, fade_out_samples: usize,
+                             shape: FadeShape) {
+    let ramp = |k: usize, len: usize| {
+        let r = k as f32 / len as f32;
+        match shape {
+            FadeShape::Linear => r,
+            FadeShape::Cosine => 0.5 - 0.5 * (std::f32::consts::PI * r).cos(),
+        }
+    };
+    let n = buf.len();
+    for (k, x) in buf.iter_mut().enumerate().take(fade_in_samples) {
+        *x *= ramp(k, fade_in_samples);
     }
-    
-    pub fn process(&mut self, input1: &RealBuffer, input2: &RealBuffer) -> &RealBuffer {
-        let n = usize::min(usize::min(input1.len(), input2.len()), self.output.len());
-        for i in 0..n {
-            self.output[i] = input1[i] + input2[i];
+    for (k, x) in buf.iter_mut().rev().enumerate().take(fade_out_samples.min(n)) {
+        *x *= ramp(k, fade_out_samples);
+    }
+}
+
+/// Number of sign changes divided by the number of samples.
+/// Samples equal to 0 are treated as positive.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::zero_crossing_rate;
+/// 
+/// assert_eq!(zero_crossing_rate(&vec![1.0, -1.0, -1.0, 1.0]), 0.5);
+/// ```
+pub fn zero_crossing_rate(buf: &RealBuffer) -> f32 {
+    if buf.is_empty() {
+        return 0.0;
+    }
+    let crossings = buf.windows(2).filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0)).count();
+    crossings as f32 / buf.len() as f32
+}
+
+/// Analytic signal computed with FFT.
+/// The real part is the input signal and the imaginary part is its Hilbert transform.
+/// The magnitude of the result is the signal envelope.
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::signals::hilbert;
+/// 
+/// let analytic = hilbert(&vec![1.0, 0.0, -1.0, 0.0]);
+/// assert_approx_eq!(analytic[0].re, 1.0, 1e-6);
+/// assert_approx_eq!(analytic[0].im, 0.0, 1e-6);
+/// assert_approx_eq!(analytic[1].im, 1.0, 1e-6);
+/// ```
+pub fn hilbert(signal: &RealBuffer) -> ComplexBuffer {
+    let n = signal.len();
+    if n == 0 {
+        return vec![];
+    }
+    let mut spectrum = padded_fft(signal, n);
+    // Double positive frequencies and remove negative ones. DC and Nyquist stay unchanged
+    for (k, c) in spectrum.iter_mut().enumerate().skip(1) {
+        if 2 * k < n {
+            *c *= 2.0;
+        } else if 2 * k > n {
+            *c = Complex32::new(0.0, 0.0);
         }
-        &self.output
     }
+    let mut output = vec![Complex32::new(0.0, 0.0); n];
+    InverseFFT::new(n).process(&mut spectrum, &mut output);
+    output.iter().map(|c| c / n as f32).collect()
+}
+
+/// Resample the signal to a new sample rate with bandlimited (windowed sinc) interpolation.
+/// The output has round(len * to_rate / from_rate) samples.
+/// When downsampling, frequencies above the new Nyquist are removed.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::signals::resample;
+/// 
+/// let output = resample(&vec![1.0; 441], 44100.0, 48000.0);
+/// assert_eq!(output.len(), 480);
+/// assert!((output[240] - 1.0).abs() < 1e-3);
+/// ```
+pub fn resample(signal: &RealBuffer, from_rate: f32, to_rate: f32) -> RealBuffer {
+    let ratio = to_rate as f64 / from_rate as f64;
+    let out_len = (signal.len() as f64 * ratio).round() as usize;
+    // Cutoff relative to the input sample rate (1.0 = input sample rate)
+    let scale = f64::min(1.0, ratio);
+    let half_width = RESAMPLING_KERNEL_HALF_WIDTH / scale;
+    (0..out_len)
+        .map(|m| {
+            let t = m as f64 / ratio;
+            let first = (t - half_width).ceil().max(0.0) as usize;
+            let last = usize::min((t + half_width).floor() as usize, signal.len().saturating_sub(1));
+            let mut acc = 0.0;
+            for (n, x) in signal.iter().enumerate().take(last + 1).skip(first) {
+                let d = t - n as f64;
+                let sinc = if d == 0.0 { 1.0 } else { (PI64 * d * scale).sin() / (PI64 * d * scale) };
+                let phase = PI64 * d / half_width;
+                let window = 0.42 + 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+                acc += *x as f64 * scale * sinc * window;
+            }
+            acc as f32
+        })
+        .collect()
+}
 
+fn padded_fft(signal: &RealBuffer, fft_size: usize) -> ComplexBuffer {
+    let mut input: ComplexBuffer = signal.iter().map(|&v| Complex32::new(v, 0.0)).collect();
+    input.resize(fft_size, Complex32::new(0.0, 0.0));
+    let mut output = vec![Complex32::new(0.0, 0.0); fft_size];
+    ForwardFFT::new(fft_size).process(&mut input, &mut output);
+    output
 }
 
 
@@ -75,7 +1155,8 @@ impl SumNode {
 mod tests {
     use assert_approx_eq::assert_approx_eq;    
     use crate::{ProcessingNode, SourceNode};
-    use crate::generators::{SineGen, StepGen, GenNode};
+    use crate::generators::{SineGen, StepGen, GenNode, WhiteNoiseGen};
+    use crate::fft::rfft;
     use super::*;
 
     #[test]
@@ -108,4 +1189,435 @@ mod tests {
         assert_approx_eq!(sum_signal[2], 1.0, 1e-5f32);
         assert_approx_eq!(sum_signal[3], 0.5, 1e-5f32);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_am_node() {
+        let mut carrier_gen = GenNode::new(Box::new(SineGen::new(250.0)), 1000.0, 1000);
+        let mut modulator_gen = GenNode::new(Box::new(SineGen::new(1.0)), 1000.0, 1000);
+        let mut am_node = ModulateAmNode::new(0.5, 1000);
+        let carrier = carrier_gen.next_frame();
+        let modulator = modulator_gen.next_frame();
+        let signal = am_node.process(carrier, modulator);
+
+        // Envelope peak is at maximum of the modulator and trough at its minimum
+        let peak = signal[240..260].iter().cloned().fold(f32::MIN, f32::max);
+        let trough = signal[740..760].iter().cloned().fold(f32::MIN, f32::max);
+        assert_approx_eq!(peak, 1.5, 1e-3f32);
+        assert_approx_eq!(trough, 0.5, 1e-3f32);
+    }
+
+    #[test]
+    fn test_convolve_impulse() {
+        let signal = vec![1.0, -2.0, 3.0, 0.5];
+        assert_eq!(convolve(&signal, &vec![1.0]), signal);
+        assert_eq!(convolve(&vec![0.0, 0.0, 1.0], &signal), vec![0.0, 0.0, 1.0, -2.0, 3.0, 0.5]);
+        assert!(convolve(&signal, &vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_convolve_fft_matches_direct() {
+        let a = GenNode::new(Box::new(WhiteNoiseGen::new(1)), 1.0, 300).next_frame().clone();
+        let b = GenNode::new(Box::new(WhiteNoiseGen::new(2)), 1.0, 100).next_frame().clone();
+        let direct = convolve_direct(&a, &b);
+        let fast = convolve_fft(&a, &b);
+        assert_eq!(direct.len(), 399);
+        assert_eq!(fast.len(), 399);
+        for (x, y) in direct.iter().zip(&fast) {
+            assert_approx_eq!(x, y, 1e-3);
+        }
+        assert_eq!(convolve(&a, &b), fast);
+    }
+
+    #[test]
+    fn test_best_lag() {
+        let signal = GenNode::new(Box::new(WhiteNoiseGen::new(3)), 1.0, 500).next_frame().clone();
+        for &k in &[0, 1, 17, 200] {
+            let mut delayed = vec![0.0; k];
+            delayed.extend(&signal[..500 - k]);
+            assert_eq!(best_lag(&delayed, &signal), k as i64);
+            assert_eq!(best_lag(&signal, &delayed), -(k as i64));
+        }
+    }
+
+    #[test]
+    fn test_estimate_pitch() {
+        let mut gen = GenNode::new(Box::new(SineGen::new(220.0)), 8000.0, 2048);
+        let pitch = estimate_pitch(gen.next_frame(), 8000.0, 50.0, 1000.0).unwrap();
+        assert_approx_eq!(pitch, 220.0, 1.0);
+    }
+
+    #[test]
+    fn test_estimate_pitch_noise() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(5)), 8000.0, 2048);
+        assert_eq!(estimate_pitch(gen.next_frame(), 8000.0, 50.0, 1000.0), None);
+        assert_eq!(estimate_pitch(&vec![0.0; 2048], 8000.0, 50.0, 1000.0), None);
+    }
+
+    #[test]
+    fn test_rms_and_peak() {
+        let mut gen = GenNode::new(Box::new(SineGen::new(10.0)), 1000.0, 1000);
+        let signal = gen.next_frame();
+        assert_approx_eq!(rms(signal), 1.0 / 2f32.sqrt(), 1e-5);
+        assert_approx_eq!(peak(signal), 1.0, 1e-5);
+        assert_approx_eq!(rms_db(signal), -3.0103, 1e-3);
+        assert_approx_eq!(peak_db(signal), 0.0, 1e-4);
+    }
+
+    #[test]
+    fn test_rms_and_peak_empty() {
+        assert_eq!(rms(&vec![]), 0.0);
+        assert_eq!(peak(&vec![]), 0.0);
+        assert_eq!(rms_db(&vec![]), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let signal = vec![0.1, -0.4, 0.2, 0.0];
+        let output = normalized(&signal, 0.8);
+        assert_approx_eq!(peak(&output), 0.8, 1e-6);
+        assert_approx_eq!(output[0], 0.2, 1e-6);
+        assert_approx_eq!(output[1], -0.8, 1e-6);
+        assert_approx_eq!(output[2], 0.4, 1e-6);
+        assert_eq!(signal[1], -0.4);
+    }
+
+    #[test]
+    fn test_normalize_zero_signal() {
+        let mut signal = vec![0.0; 4];
+        normalize(&mut signal, 1.0);
+        assert_eq!(signal, vec![0.0; 4]);
+    }
+
+    #[test]
+    fn test_clip_node() {
+        let mut gen = GenNode::new(Box::new(SineGen::new(1.0)), 16.0, 16);
+        let mut clip_node = ClipNode::new(0.5, 16);
+        let signal = gen.next_frame().clone();
+        let output = clip_node.process(&signal);
+        assert_approx_eq!(output[4], 0.5, 1e-6);
+        assert_approx_eq!(output[12], -0.5, 1e-6);
+        for (x, y) in signal.iter().zip(output) {
+            if x.abs() <= 0.5 {
+                assert_eq!(x, y);
+            } else {
+                assert_eq!(y.abs(), 0.5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_soft_clip_node() {
+        let mut clip_node = SoftClipNode::new(2.0, 4);
+        let output = clip_node.process(&vec![0.001, -0.002, 10.0, -10.0]);
+        assert_approx_eq!(output[0], 0.002, 1e-6);
+        assert_approx_eq!(output[1], -0.004, 1e-6);
+        assert!(output[2] <= 1.0 && output[2] > 0.9999);
+        assert!(output[3] >= -1.0 && output[3] < -0.9999);
+    }
+
+    #[test]
+    fn test_zero_crossing_rate() {
+        assert_eq!(zero_crossing_rate(&vec![0.5; 100]), 0.0);
+        assert_eq!(zero_crossing_rate(&vec![0.0; 100]), 0.0);
+        let alternating: RealBuffer = (0..100).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }).collect();
+        assert_approx_eq!(zero_crossing_rate(&alternating), 0.99, 1e-6);
+        assert_eq!(zero_crossing_rate(&vec![]), 0.0);
+    }
+
+    #[test]
+    fn test_hilbert_envelope() {
+        let mut gen = GenNode::new(Box::new(SineGen::new(50.0)), 1000.0, 1000);
+        let signal = gen.next_frame().clone();
+        let analytic = hilbert(&signal);
+        assert_eq!(analytic.len(), 1000);
+        for (c, x) in analytic.iter().zip(&signal) {
+            assert_approx_eq!(c.re, x, 1e-4);
+            assert_approx_eq!(c.norm(), 1.0, 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_envelope_follower_burst() {
+        // Attack 10 samples, release 1000 samples
+        let mut follower = EnvelopeFollowerNode::new(0.001, 0.1, 10000.0, 10);
+        let burst = vec![1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0];
+        assert_approx_eq!(follower.process(&burst)[9], 1.0 - (-1f32).exp(), 1e-3);
+        for _ in 0..9 {
+            follower.process(&burst);
+        }
+        assert_approx_eq!(follower.process(&burst)[9], 1.0, 1e-3);
+        let silence = vec![0.0; 10];
+        for _ in 0..99 {
+            follower.process(&silence);
+        }
+        assert_approx_eq!(follower.process(&silence)[9], (-1f32).exp(), 1e-3);
+    }
+
+    #[test]
+    fn test_delay_node_integer() {
+        let signal = GenNode::new(Box::new(WhiteNoiseGen::new(7)), 1.0, 40).next_frame().clone();
+        let mut delay = DelayNode::new(3.0, 8, 10);
+        let mut output: RealBuffer = vec![];
+        for frame in signal.chunks(10) {
+            output.extend(delay.process(&frame.to_vec()));
+        }
+        assert_eq!(&output[..3], &[0.0, 0.0, 0.0]);
+        assert_eq!(&output[3..], &signal[..37]);
+    }
+
+    #[test]
+    fn test_delay_node_fractional() {
+        let mut delay = DelayNode::new(0.5, 2, 4);
+        assert_eq!(delay.process(&vec![1.0, 3.0, 5.0, 7.0]), &vec![0.5, 2.0, 4.0, 6.0]);
+        assert_eq!(delay.process(&vec![9.0, 0.0, 0.0, 0.0]), &vec![8.0, 4.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_decimate_node() {
+        // New sample rate is 2000 Hz. 2500 Hz would alias to 500 Hz without filtering
+        let mut decimate = DecimateNode::new(4, 1024);
+        let mut gen = GenNode::new(Box::new(SineGen::new(2500.0)), 8000.0, 1024);
+        decimate.process(gen.next_frame());
+        assert!(peak(decimate.process(gen.next_frame())) < 0.01);
+
+        let mut decimate = DecimateNode::new(4, 1024);
+        let mut gen = GenNode::new(Box::new(SineGen::new(250.0)), 8000.0, 1024);
+        decimate.process(gen.next_frame());
+        let output = decimate.process(gen.next_frame());
+        assert_eq!(output.len(), 256);
+        assert_approx_eq!(rms(output), 1.0 / 2f32.sqrt(), 0.01);
+    }
+
+    #[test]
+    fn test_interpolate_node() {
+        // 1000 Hz at 4000 Hz upsampled to 8000 Hz. Without filtering there is an image at 3000 Hz
+        let mut interpolate = InterpolateNode::new(2, 512);
+        let mut gen = GenNode::new(Box::new(SineGen::new(1000.0)), 4000.0, 512);
+        interpolate.process(gen.next_frame());
+        let output = interpolate.process(gen.next_frame());
+        assert_eq!(output.len(), 1024);
+        assert_approx_eq!(peak(output), 1.0, 0.01);
+        // Bin width is 7.8125 Hz
+        let spectrum = rfft(output);
+        assert!(spectrum[384].norm() < 0.001 * spectrum[128].norm());
+    }
+
+    #[test]
+    fn test_resample_preserves_frequency() {
+        let mut gen = GenNode::new(Box::new(SineGen::new(1000.0)), 44100.0, 4410);
+        let output = resample(gen.next_frame(), 44100.0, 48000.0);
+        assert_eq!(output.len(), 4800);
+        let middle = output[1000..3000].to_vec();
+        assert_approx_eq!(estimate_pitch(&middle, 48000.0, 100.0, 4000.0).unwrap(), 1000.0, 1.0);
+        assert_approx_eq!(peak(&middle), 1.0, 0.01);
+    }
+
+    #[test]
+    fn test_resample_down() {
+        let mut gen = GenNode::new(Box::new(SineGen::new(1000.0)), 48000.0, 4800);
+        let output = resample(gen.next_frame(), 48000.0, 8000.0);
+        assert_eq!(output.len(), 800);
+        let middle = output[100..700].to_vec();
+        assert_approx_eq!(estimate_pitch(&middle, 8000.0, 100.0, 2000.0).unwrap(), 1000.0, 1.0);
+        // 5000 Hz is above the new Nyquist and should be removed
+        let mut gen = GenNode::new(Box::new(SineGen::new(5000.0)), 48000.0, 4800);
+        let output = resample(gen.next_frame(), 48000.0, 8000.0);
+        assert!(peak(&output[100..700].to_vec()) < 0.01);
+    }
+
+    #[test]
+    fn test_differentiator_ramp() {
+        // Ramp with slope 3 per second sampled at 100 Hz
+        let mut diff = DifferentiatorNode::new(100.0, 10);
+        let ramp = |frame: usize| -> RealBuffer {
+            (0..10).map(|i| 3.0 * (10 * frame + i) as f32 / 100.0).collect()
+        };
+        let output = diff.process(&ramp(0));
+        assert_eq!(output[0], 0.0);
+        for y in &output[1..] {
+            assert_approx_eq!(y, 3.0, 1e-4);
+        }
+        for y in diff.process(&ramp(1)) {
+            assert_approx_eq!(y, 3.0, 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_integrator_constant() {
+        let mut integrator = IntegratorNode::new(100.0, 10);
+        let constant = vec![2.0; 10];
+        integrator.process(&constant);
+        let output = integrator.process(&constant);
+        for (i, y) in output.iter().enumerate() {
+            assert_approx_eq!(y, 2.0 * (11 + i) as f32 / 100.0, 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_leaky_integrator() {
+        let mut integrator = IntegratorNode::with_leak(1.0, 0.5, 10);
+        let output = integrator.process(&vec![1.0; 10]);
+        assert_approx_eq!(output[0], 1.0, 1e-6);
+        assert_approx_eq!(output[9], 2.0, 1e-2);
+    }
+
+    #[test]
+    fn test_gain_node_f64() {
+        let mut gain_node = GainNode::new(0.1f64, 3);
+        let output = gain_node.process(&vec![1.0, 2.0, 3.0]);
+        assert_eq!(output, &vec![0.1, 0.2, 0.30000000000000004]);
+    }
+
+    #[test]
+    fn test_sum_node_many() {
+        let mut gens: Vec<GenNode> = [1.0, 2.0, 3.0, 5.0].iter()
+            .map(|&f| GenNode::new(Box::new(SineGen::new(f)), 32.0, 32))
+            .collect();
+        let signals: Vec<RealBuffer> = gens.iter_mut().map(|g| g.next_frame().clone()).collect();
+        let inputs: Vec<&RealBuffer> = signals.iter().collect();
+        let mut sum_node = SumNode::new(32);
+        let output = sum_node.process_many(&inputs);
+        for i in 0..32 {
+            let expected = signals[0][i] + signals[1][i] + signals[2][i] + signals[3][i];
+            assert_approx_eq!(output[i], expected, 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_diff_node() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(11)), 16.0, 16);
+        let mut diff_node = DiffNode::new(16);
+        let signal = gen.next_frame();
+        assert_eq!(diff_node.process(signal, signal), &vec![0.0; 16]);
+    }
+
+    #[test]
+    fn test_multiply_node() {
+        // sin(a)*sin(b) = (cos(a-b) - cos(a+b)) / 2
+        let mut gen1 = GenNode::new(Box::new(SineGen::new(100.0)), 1000.0, 1000);
+        let mut gen2 = GenNode::new(Box::new(SineGen::new(30.0)), 1000.0, 1000);
+        let mut multiply_node = MultiplyNode::new(1000);
+        let spectrum = rfft(multiply_node.process(gen1.next_frame(), gen2.next_frame()));
+        for (k, c) in spectrum.iter().enumerate() {
+            let amplitude = 2.0 * c.norm() / 1000.0;
+            if k == 70 || k == 130 {
+                assert_approx_eq!(amplitude, 0.5, 1e-3);
+            } else {
+                assert!(amplitude < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mix_node() {
+        let mut gen1 = GenNode::new(Box::new(SineGen::new(1.0)), 8.0, 8);
+        let mut gen2 = GenNode::new(Box::new(StepGen::new(0.5)), 8.0, 8);
+        let a = gen1.next_frame().clone();
+        let b = gen2.next_frame().clone();
+        let mut mix_node = MixNode::new(vec![0.25, 0.75], 8);
+        let output = mix_node.process(&[&a, &b]);
+        for i in 0..8 {
+            assert_approx_eq!(output[i], 0.25 * a[i] + 0.75 * b[i], 1e-6);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mix_node_wrong_inputs() {
+        let mut mix_node = MixNode::new(vec![0.25, 0.75], 2);
+        mix_node.process(&[&vec![1.0, 2.0]]);
+    }
+
+    #[test]
+    fn test_try_process() {
+        let mut gain_node = GainNode::new(2.0, 2);
+        assert_eq!(gain_node.try_process(&vec![1.0, 2.0]), Ok(&vec![2.0, 4.0]));
+        assert_eq!(gain_node.try_process(&vec![1.0, 2.0, 3.0]),
+                   Err(DspError::SizeMismatch { expected: 2, got: 3 }));
+        let mut sum_node = SumNode::new(2);
+        assert_eq!(sum_node.try_process(&vec![1.0, 2.0], &vec![1.0, 1.0]), Ok(&vec![2.0, 3.0]));
+        assert_eq!(sum_node.try_process(&vec![1.0, 2.0], &vec![1.0]),
+                   Err(DspError::SizeMismatch { expected: 2, got: 1 }));
+    }
+
+    #[test]
+    fn test_compressor() {
+        let mut compressor = CompressorNode::new(-20.0, 4.0, 0.001, 0.05, 1000.0, 100);
+        // -6 dBFS is 14 dB over the threshold and is reduced to 3.5 dB over it
+        for _ in 0..2 {
+            let output = compressor.process(&vec![0.5; 100]);
+            assert_approx_eq!(20.0 * output[99].log10(), -20.0 + (20.0 * 0.5f32.log10() + 20.0) / 4.0, 1e-3);
+        }
+        // Signal below the threshold passes unchanged once the envelope has been released
+        let mut output = vec![];
+        for _ in 0..10 {
+            output = compressor.process(&vec![0.05; 100]).clone();
+        }
+        assert_approx_eq!(output[99], 0.05, 1e-6);
+        compressor.set_makeup_gain(6.0);
+        assert_approx_eq!(compressor.process(&vec![0.05; 100])[99], 0.05 * 10f32.powf(0.3), 1e-6);
+    }
+
+    #[test]
+    fn test_gate() {
+        let sample_rate = 8000.0;
+        let mut tone = GenNode::new(Box::new(SineGen::new(200.0)), sample_rate, 800);
+        let mut noise = GenNode::new(Box::new(WhiteNoiseGen::new(7)), sample_rate, 1600);
+        // 0.1 s burst at -6 dBFS followed by a quiet tail at -60 dBFS
+        let mut input: RealBuffer = tone.next_frame().iter().map(|v| 0.5 * v).collect();
+        input.extend(noise.next_frame().iter().map(|v| 0.001 * v));
+        let mut gate = GateNode::new(-40.0, 0.001, 0.01, 0.01, sample_rate, 400);
+        let mut output: RealBuffer = vec![];
+        for frame in input.chunks(400) {
+            output.extend(gate.process(&frame.to_vec()));
+        }
+        for i in 100..800 {
+            assert_approx_eq!(output[i], input[i], 1e-3);
+        }
+        // Tail after hold and several release time constants
+        let tail_start = 800 + 80 + 800;
+        let tail_in = rms(&input[tail_start..].to_vec());
+        let tail_out = rms(&output[tail_start..].to_vec());
+        assert!(tail_out < 0.01 * tail_in);
+    }
+
+    #[test]
+    fn test_apply_fade() {
+        for &shape in &[FadeShape::Linear, FadeShape::Cosine] {
+            let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(3)), 100.0, 100);
+            let original = gen.next_frame().clone();
+            let mut signal = original.clone();
+            apply_fade_with_shape(&mut signal, 10, 20, shape);
+            assert_eq!(signal[0], 0.0);
+            assert_eq!(signal[99], 0.0);
+            assert_eq!(signal[10..80], original[10..80]);
+            assert!(signal.iter().zip(&original).all(|(x, y)| x.abs() <= y.abs()));
+        }
+        // Overlapping fades
+        let mut signal = vec![1.0; 5];
+        apply_fade(&mut signal, 4, 8);
+        assert_eq!(signal[0], 0.0);
+        assert_eq!(signal[4], 0.0);
+        assert_approx_eq!(signal[2], 0.5 * 0.25, 1e-6);
+        let mut short = vec![1.0; 3];
+        apply_fade_with_shape(&mut short, 10, 10, FadeShape::Cosine);
+        assert_eq!(short[0], 0.0);
+        assert_eq!(short[2], 0.0);
+        assert!(short[1] < 0.01);
+    }
+
+    #[test]
+    fn test_gate_without_hold() {
+        // Envelope keeps the gate open around zero crossings of the tone
+        let mut tone = GenNode::new(Box::new(SineGen::new(200.0)), 8000.0, 400);
+        let mut gate = GateNode::new(-40.0, 0.001, 0.0, 0.01, 8000.0, 400);
+        for frame in 0..3 {
+            let input: RealBuffer = tone.next_frame().iter().map(|v| 0.5 * v).collect();
+            let output = gate.process(&input);
+            let start = if frame == 0 { 200 } else { 0 };
+            for i in start..400 {
+                assert_approx_eq!(output[i], input[i], 1e-3);
+            }
+        }
+    }
+}
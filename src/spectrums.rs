@@ -1,14 +1,56 @@
 //! Analyze discrete signal in frequency domain using complex numbers
 
-use crate::ComplexBuffer;
+use crate::num_complex::Complex32;
+use crate::{ComplexBuffer, RealBuffer, ProcessingNode};
+use crate::fft::{ForwardFFT, InverseFFT, dct2_ortho};
+use crate::windows::{hann_periodic, Window};
 use crate::vectors;
 
+// Floor added to magnitude before taking logarithm
+const LOG_FLOOR: f32 = 1e-10;
+
 
 /// Calculated frequency of a given component
 pub fn item_freq(i: usize, spectrum : &ComplexBuffer, sample_rate: usize) -> f32 {
     (i * sample_rate) as f32 / (spectrum.len() as f32)
 }
 
+/// Convert FFT bin index into frequency in Hz.
+/// Bins above N/2 represent negative frequencies. Nyquist bin N/2 (for even N) is +sample_rate/2.
+///   * bin - Bin index
+///   * n - FFT size
+///   * sample_rate - Samples per second
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// 
+/// assert_eq!(spectrums::bin_to_hz(2, 8, 16.0), 4.0);
+/// assert_eq!(spectrums::bin_to_hz(4, 8, 16.0), 8.0);
+/// assert_eq!(spectrums::bin_to_hz(6, 8, 16.0), -4.0);
+/// ```
+pub fn bin_to_hz(bin: usize, n: usize, sample_rate: f32) -> f32 {
+    let bin = bin % n;
+    if bin <= n / 2 {
+        bin as f32 * sample_rate / n as f32
+    } else {
+        -((n - bin) as f32) * sample_rate / n as f32
+    }
+}
+
+/// Convert frequency in Hz into the nearest FFT bin index.
+/// Negative frequencies map to bins above N/2.
+pub fn hz_to_bin(freq: f32, n: usize, sample_rate: f32) -> usize {
+    let bin = (freq * n as f32 / sample_rate).round() as i64;
+    bin.rem_euclid(n as i64) as usize
+}
+
+/// Frequency in Hz of each FFT bin. See bin_to_hz.
+pub fn frequencies(n: usize, sample_rate: f32) -> RealBuffer {
+    (0..n).map(|i| bin_to_hz(i, n, sample_rate)).collect()
+}
+
 /// Return max frequency
 pub fn max_freq(spectrum : &ComplexBuffer, sample_rate: usize) -> f32 {
     let idx = vectors::argmax(&spectrum);
@@ -18,3 +60,1130 @@ pub fn max_freq(spectrum : &ComplexBuffer, sample_rate: usize) -> f32 {
         item_freq(spectrum.len() - idx, &spectrum, sample_rate)
     }
 }
+
+/// Spectral centroid: magnitude weighted mean frequency.
+/// Only positive frequencies (bins 0..=N/2) of the spectrum are used.
+/// Returns 0 for the spectrum without energy.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let mut spectrum = vec![Complex32::new(0.0, 0.0); 8];
+/// spectrum[1] = Complex32::new(1.0, 0.0);
+/// spectrum[3] = Complex32::new(1.0, 0.0);
+/// assert_eq!(spectrums::spectral_centroid(&spectrum, 8.0), 2.0);
+/// ```
+pub fn spectral_centroid(spectrum: &ComplexBuffer, sample_rate: f32) -> f32 {
+    let n = spectrum.len();
+    let mut weighted_sum = 0.0;
+    let mut total = 0.0;
+    for (i, c) in spectrum.iter().enumerate().take(n / 2 + 1) {
+        let mag = c.norm();
+        weighted_sum += mag * i as f32 * sample_rate / n as f32;
+        total += mag;
+    }
+    if total > 0.0 { weighted_sum / total } else { 0.0 }
+}
+
+/// Spectral flatness (Wiener entropy): ratio of the geometric mean to the arithmetic mean
+/// of the magnitude spectrum. Only positive frequencies (bins 0..=N/2) are used.
+/// Value is close to 1 for noise-like signals and close to 0 for tonal ones.
+/// Returns 0 for the spectrum without energy.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let spectrum = vec![Complex32::new(1.0, 0.0); 8];
+/// assert!((spectrums::spectral_flatness(&spectrum) - 1.0).abs() < 1e-6);
+/// ```
+pub fn spectral_flatness(spectrum: &ComplexBuffer) -> f32 {
+    let bins = spectrum.len() / 2 + 1;
+    let mags: RealBuffer = spectrum.iter().take(bins).map(|c| c.norm()).collect();
+    let n = mags.len() as f32;
+    let mean = mags.iter().sum::<f32>() / n;
+    if mean <= 0.0 {
+        return 0.0;
+    }
+    let log_mean = mags.iter().map(|m| (m + LOG_FLOOR).ln()).sum::<f32>() / n;
+    (log_mean.exp() / mean).min(1.0)
+}
+
+/// Spectral rolloff: frequency below which the given fraction of the total spectral energy lies.
+/// Only positive frequencies (bins 0..=N/2) are used. Returns 0 for the spectrum without energy.
+///   * percent - Fraction of energy between 0 and 1, typically 0.85
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let mut spectrum = vec![Complex32::new(0.0, 0.0); 8];
+/// spectrum[1] = Complex32::new(1.0, 0.0);
+/// spectrum[3] = Complex32::new(1.0, 0.0);
+/// assert_eq!(spectrums::spectral_rolloff(&spectrum, 8.0, 0.85), 3.0);
+/// ```
+pub fn spectral_rolloff(spectrum: &ComplexBuffer, sample_rate: f32, percent: f32) -> f32 {
+    let n = spectrum.len();
+    let energies: RealBuffer = spectrum.iter().take(n / 2 + 1).map(|c| c.norm_sqr()).collect();
+    let threshold = percent * energies.iter().sum::<f32>();
+    if threshold <= 0.0 {
+        return 0.0;
+    }
+    let mut cumulative = 0.0;
+    for (i, e) in energies.iter().enumerate() {
+        cumulative += e;
+        if cumulative >= threshold {
+            return bin_to_hz(i, n, sample_rate);
+        }
+    }
+    bin_to_hz(energies.len() - 1, n, sample_rate)
+}
+
+/// Find the strongest peaks in the spectrum.
+/// Peak frequency and magnitude are refined with parabolic interpolation
+/// of the log magnitude around local maximum.
+/// Only positive frequencies are searched. DC and Nyquist bins are skipped.
+/// 
+/// Returns up to max_peaks pairs of (frequency, magnitude), sorted by magnitude.
+///   * min_ratio - Peak magnitude has to be at least min_ratio times larger than its smaller neighbor
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let mut spectrum = vec![Complex32::new(0.1, 0.0); 16];
+/// spectrum[3] = Complex32::new(1.0, 0.0);
+/// spectrum[5] = Complex32::new(2.0, 0.0);
+/// let peaks = spectrums::find_peaks(&spectrum, 16.0, 1, 2.0);
+/// assert_eq!(peaks, vec![(5.0, 2.0)]);
+/// ```
+pub fn find_peaks(spectrum: &ComplexBuffer, sample_rate: f32, max_peaks: usize, min_ratio: f32) -> Vec<(f32, f32)> {
+    let n = spectrum.len();
+    let mags = magnitude(spectrum);
+    let mut peaks = Vec::new();
+    for k in 1..n - n / 2 {
+        let (left, mag, right) = (mags[k-1], mags[k], mags[k+1]);
+        if mag <= left || mag < right || mag < min_ratio * f32::min(left, right) {
+            continue;
+        }
+        // Parabola through log magnitudes of 3 points
+        let (a, b, c) = (left.max(f32::MIN_POSITIVE).ln(), mag.ln(), right.max(f32::MIN_POSITIVE).ln());
+        let denom = a - 2.0 * b + c;
+        let p = if denom != 0.0 { 0.5 * (a - c) / denom } else { 0.0 };
+        let peak_freq = (k as f32 + p) * sample_rate / n as f32;
+        let peak_mag = (b - 0.25 * (a - c) * p).exp();
+        peaks.push((peak_freq, peak_mag));
+    }
+    peaks.sort_by(|x, y| y.1.partial_cmp(&x.1).unwrap());
+    peaks.truncate(max_peaks);
+    peaks
+}
+
+/// Total harmonic distortion.
+/// Ratio of the RMS amplitude of harmonics to the amplitude of the fundamental frequency.
+/// Each component is taken from the strongest bin within ±1 bin of its expected position.
+///   * fundamental_freq - Frequency in Hz of the fundamental
+///   * num_harmonics - Number of harmonics (starting from the 2nd) to include.
+///     Harmonics above Nyquist frequency are ignored.
+/// 
+/// Returns 0 if the fundamental is not in range (0, Nyquist] or has no energy, or the spectrum is empty.
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let mut spectrum = vec![Complex32::new(0.0, 0.0); 16];
+/// spectrum[2] = Complex32::new(1.0, 0.0);
+/// spectrum[4] = Complex32::new(0.3, 0.0);
+/// spectrum[6] = Complex32::new(0.4, 0.0);
+/// assert_approx_eq!(spectrums::thd(&spectrum, 2.0, 16.0, 2), 0.5, 1e-5f32);
+/// ```
+pub fn thd(spectrum: &ComplexBuffer, fundamental_freq: f32, sample_rate: f32, num_harmonics: usize) -> f32 {
+    let n = spectrum.len();
+    let component_power = |freq: f32| -> f32 {
+        let bin = hz_to_bin(freq, n, sample_rate);
+        let start = bin.saturating_sub(1);
+        let end = usize::min(bin + 1, n / 2);
+        spectrum[start..=end].iter().map(|c| c.norm_sqr()).fold(0.0, f32::max)
+    };
+    if n == 0 || fundamental_freq <= 0.0 || fundamental_freq > sample_rate / 2.0 {
+        return 0.0;
+    }
+    let fundamental = component_power(fundamental_freq);
+    if fundamental == 0.0 {
+        return 0.0;
+    }
+    let harmonics: f32 = (2..num_harmonics + 2)
+        .map(|h| h as f32 * fundamental_freq)
+        .filter(|&freq| freq <= sample_rate / 2.0)
+        .map(component_power)
+        .sum();
+    (harmonics / fundamental).sqrt()
+}
+
+/// Signal to noise ratio in dB.
+/// Power of the given bins is treated as signal and power of all other bins as noise.
+/// Only positive frequencies (bins 0..=N/2) are used, so signal_bins should be in this range.
+/// Returns +inf when there is no noise.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let mut spectrum = vec![Complex32::new(0.0, 0.0); 16];
+/// spectrum[2] = Complex32::new(10.0, 0.0);
+/// spectrum[5] = Complex32::new(1.0, 0.0);
+/// assert_eq!(spectrums::snr(&spectrum, &[2]), 20.0);
+/// ```
+pub fn snr(spectrum: &ComplexBuffer, signal_bins: &[usize]) -> f32 {
+    let (signal, noise) = split_power(spectrum, signal_bins);
+    10.0 * (signal / noise).log10()
+}
+
+/// Signal to noise and distortion ratio in dB.
+/// Ratio of the total power to the power of everything except the fundamental bins.
+/// Unlike snr, harmonics are counted as distortion, so they should not be part of fundamental_bins.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let mut spectrum = vec![Complex32::new(0.0, 0.0); 16];
+/// spectrum[2] = Complex32::new(3.0, 0.0);
+/// spectrum[4] = Complex32::new(1.0, 0.0);
+/// assert_eq!(spectrums::sinad(&spectrum, &[2]), 10.0);
+/// ```
+pub fn sinad(spectrum: &ComplexBuffer, fundamental_bins: &[usize]) -> f32 {
+    let (signal, rest) = split_power(spectrum, fundamental_bins);
+    10.0 * ((signal + rest) / rest).log10()
+}
+
+/// Power of the selected positive frequency bins and of all remaining ones
+fn split_power(spectrum: &ComplexBuffer, bins: &[usize]) -> (f32, f32) {
+    spectrum.iter().enumerate().take(spectrum.len() / 2 + 1)
+        .fold((0.0, 0.0), |(selected, rest), (i, c)| {
+            if bins.contains(&i) {
+                (selected + c.norm_sqr(), rest)
+            } else {
+                (selected, rest + c.norm_sqr())
+            }
+        })
+}
+
+/// Magnitude |z| of each spectrum bin
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let spectrum = vec![Complex32::new(3.0, 4.0), Complex32::new(0.0, -2.0)];
+/// assert_eq!(spectrums::magnitude(&spectrum), vec![5.0, 2.0]);
+/// ```
+pub fn magnitude(spectrum: &ComplexBuffer) -> RealBuffer {
+    spectrum.iter().map(|c| c.norm()).collect()
+}
+
+/// Phase atan2(im, re) of each spectrum bin in range [-π, π]
+pub fn phase(spectrum: &ComplexBuffer) -> RealBuffer {
+    spectrum.iter().map(|c| c.arg()).collect()
+}
+
+
+/// Unwrap phase by removing 2π jumps between consecutive values.
+///   * threshold - Jumps larger than threshold are corrected. Usually π.
+/// 
+/// Example
+/// 
+/// ```
+/// use std::f32::consts::PI;
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::spectrums;
+/// 
+/// let unwrapped = spectrums::unwrap(&vec![0.9 * PI, -0.9 * PI], PI);
+/// assert_approx_eq!(unwrapped[1], 1.1 * PI, 1e-5f32);
+/// ```
+pub fn unwrap(phase: &RealBuffer, threshold: f32) -> RealBuffer {
+    let two_pi = 2.0 * std::f32::consts::PI;
+    let mut output = Vec::with_capacity(phase.len());
+    let mut offset = 0.0;
+    for i in 0..phase.len() {
+        if i > 0 {
+            let diff = phase[i] - phase[i-1];
+            if diff > threshold {
+                offset -= two_pi * ((diff - threshold) / two_pi).ceil();
+            } else if diff < -threshold {
+                offset += two_pi * ((-diff - threshold) / two_pi).ceil();
+            }
+        }
+        output.push(phase[i] + offset);
+    }
+    output
+}
+
+/// Magnitude of each spectrum bin in decibels: 20*log10(|z|/ref_level)
+///   * ref_level - Magnitude which maps to 0 dB
+///   * min_db - Lowest returned value. Used for bins with zero magnitude instead of -inf.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let spectrum = vec![Complex32::new(10.0, 0.0), Complex32::new(0.0, 0.0)];
+/// assert_eq!(spectrums::magnitude_db(&spectrum, 1.0, -100.0), vec![20.0, -100.0]);
+/// ```
+pub fn magnitude_db(spectrum: &ComplexBuffer, ref_level: f32, min_db: f32) -> RealBuffer {
+    spectrum.iter()
+        .map(|c| (20.0 * (c.norm() / ref_level).log10()).max(min_db))
+        .collect()
+}
+
+/// A-weighting gain in dB at the given frequency, as defined by IEC 61672.
+/// Curve is normalized so the gain at 1 kHz is 0 dB. Returns -inf at 0 Hz.
+/// https://en.wikipedia.org/wiki/A-weighting
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// 
+/// assert!(spectrums::a_weighting_db(1000.0).abs() < 0.01);
+/// ```
+pub fn a_weighting_db(freq: f32) -> f32 {
+    let f2 = (freq as f64).powi(2);
+    let r = 12194f64.powi(2) * f2 * f2
+        / ((f2 + 20.6f64.powi(2))
+            * ((f2 + 107.7f64.powi(2)) * (f2 + 737.9f64.powi(2))).sqrt()
+            * (f2 + 12194f64.powi(2)));
+    (20.0 * r.log10() + 2.0) as f32
+}
+
+/// Apply A-weighting to the full FFT spectrum.
+/// Each bin is scaled by the A-weighting gain of its (absolute) frequency, so negative
+/// frequencies are weighted the same way and the spectrum of a real signal stays symmetric.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let spectrum = vec![Complex32::new(1.0, 0.0); 4];
+/// let weighted = spectrums::a_weighting(&spectrum, 4000.0);
+/// assert_eq!(weighted[0].re, 0.0);
+/// assert!((weighted[1].re - 1.0).abs() < 1e-3);
+/// ```
+pub fn a_weighting(spectrum: &ComplexBuffer, sample_rate: f32) -> ComplexBuffer {
+    let n = spectrum.len();
+    spectrum.iter().enumerate()
+        .map(|(i, c)| {
+            let freq = bin_to_hz(i, n, sample_rate).abs();
+            c * 10f32.powf(a_weighting_db(freq) / 20.0)
+        })
+        .collect()
+}
+
+/// Power summed over the octave or third-octave bands.
+/// Center frequencies follow the base 10 system from IEC 61260: f = 1000 * 10^(3k / (10 * fraction)),
+/// with band edges at f * 10^(+-3 / (20 * fraction)). Exact (not nominal) center frequencies are returned.
+/// Only the bands which fit between the first FFT bin and Nyquist frequency are included.
+/// 
+/// Returns (center frequency, band power) pairs in increasing frequency order.
+/// Band power is the sum of squared magnitudes of the positive frequency bins in the band.
+///   * fraction - 1 for octave bands, 3 for third-octave bands
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// use dsp::num_complex::Complex32;
+/// 
+/// let mut spectrum = vec![Complex32::new(0.0, 0.0); 8192];
+/// spectrum[1000] = Complex32::new(1.0, 0.0);
+/// let bands = spectrums::octave_bands(&spectrum, 8192.0, 1);
+/// let (center, power) = bands.iter().cloned().fold((0.0, 0.0), |a, b| if b.1 > a.1 { b } else { a });
+/// assert!((center - 1000.0).abs() < 1e-2);
+/// assert_eq!(power, 1.0);
+/// ```
+pub fn octave_bands(spectrum: &ComplexBuffer, sample_rate: f32, fraction: u8) -> Vec<(f32, f32)> {
+    assert!(fraction == 1 || fraction == 3, "Only octave (1) and third-octave (3) bands are supported");
+    let n = spectrum.len();
+    let resolution = sample_rate / n as f32;
+    let nyquist = sample_rate / 2.0;
+    let ratio = 10f32.powf(0.3 / fraction as f32);
+    let half_band = ratio.sqrt();
+    // Lowest band index with the lower edge above the first bin
+    let lowest = ((resolution * half_band / 1000.0).log(ratio)).ceil() as i32;
+    (lowest..)
+        .map(|k| 1000.0 * ratio.powi(k))
+        .take_while(|center| center * half_band <= nyquist)
+        .map(|center| {
+            let lower = center / half_band;
+            let upper = center * half_band;
+            let power = spectrum.iter().enumerate().take(n / 2 + 1)
+                .filter(|&(i, _)| {
+                    let freq = i as f32 * resolution;
+                    freq >= lower && freq < upper
+                })
+                .map(|(_, c)| c.norm_sqr())
+                .sum();
+            (center, power)
+        })
+        .collect()
+}
+
+/// Estimate power spectral density with Welch's method.
+/// Signal is split into overlapping segments. Each segment is windowed and transformed with FFT.
+/// Squared magnitudes are averaged over all segments.
+/// https://en.wikipedia.org/wiki/Welch%27s_method
+/// 
+/// Returns one-sided PSD with segment_len/2+1 bins, normalized by the window power.
+/// Values are for sample rate 1. Divide them by the sample rate to get power per Hz.
+///   * segment_len - Length of each segment. Should be equal to the window length
+///   * overlap - Number of samples shared by the consecutive segments
+///   * window - Window applied to each segment
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::{spectrums, windows};
+/// 
+/// let signal = vec![1.0; 64];
+/// let psd = spectrums::welch_psd(&signal, 16, 8, &windows::hann(16, 0, 16));
+/// assert_eq!(psd.len(), 9);
+/// assert!(psd[0] > psd[4]);
+/// ```
+pub fn welch_psd(signal: &RealBuffer, segment_len: usize, overlap: usize, window: &Window) -> RealBuffer {
+    assert!(overlap < segment_len, "Overlap should be smaller than segment length");
+    assert_eq!(window.len(), segment_len);
+    let mut fft = ForwardFFT::new(segment_len);
+    let mut input = vec![Complex32::new(0.0, 0.0); segment_len];
+    let mut output = vec![Complex32::new(0.0, 0.0); segment_len];
+    let mut psd = vec![0.0; segment_len / 2 + 1];
+    let hop = segment_len - overlap;
+    let mut num_segments = 0;
+    let mut start = 0;
+    while start + segment_len <= signal.len() {
+        let segment = &signal[start..start + segment_len];
+        for ((c, &v), &w) in input.iter_mut().zip(segment).zip(window.samples()) {
+            *c = Complex32::new(v * w, 0.0);
+        }
+        fft.process(&mut input, &mut output);
+        for (p, c) in psd.iter_mut().zip(output.iter()) {
+            *p += c.norm_sqr();
+        }
+        num_segments += 1;
+        start += hop;
+    }
+    if num_segments == 0 {
+        return psd;
+    }
+    let window_power: f32 = window.samples().iter().map(|w| w * w).sum();
+    let scale = 1.0 / (num_segments as f32 * window_power);
+    let last = psd.len() - 1;
+    for (i, p) in psd.iter_mut().enumerate() {
+        // One-sided spectrum: double bins which have negative frequency pair
+        let one_sided = if i == 0 || (i == last && 2 * last == segment_len) { 1.0 } else { 2.0 };
+        *p *= scale * one_sided;
+    }
+    psd
+}
+
+/// Estimate magnitude-squared coherence |Pxy|^2 / (Pxx*Pyy) of two signals.
+/// Spectra are averaged with Welch's method using periodic Hann window.
+/// Returns segment_len/2+1 values in range [0, 1].
+/// Note that with a single segment coherence is always 1.
+///   * segment_len - Length of each segment
+///   * overlap - Number of samples shared by the consecutive segments
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums;
+/// 
+/// let x: Vec<f32> = (0..256).map(|i| ((i * i) % 17) as f32).collect();
+/// let y: Vec<f32> = x.iter().map(|v| 0.5 * v).collect();
+/// let c = spectrums::coherence(&x, &y, 64, 32);
+/// assert_eq!(c.len(), 33);
+/// assert!(c.iter().all(|&v| v > 0.99));
+/// ```
+pub fn coherence(x: &RealBuffer, y: &RealBuffer, segment_len: usize, overlap: usize) -> RealBuffer {
+    let (pxx, pyy, pxy) = welch_cross_spectra(x, y, segment_len, overlap);
+    pxx.iter().zip(&pyy).zip(&pxy)
+        .map(|((&sxx, &syy), sxy)| {
+            let denominator = sxx * syy;
+            if denominator > 0.0 { (sxy.norm_sqr() / denominator).min(1.0) } else { 0.0 }
+        })
+        .collect()
+}
+
+/// Estimate frequency response of a system from its input and output (H1 estimator Pxy/Pxx).
+/// Spectra are averaged with Welch's method using periodic Hann window.
+/// Returns segment_len/2+1 bins. Bins where input has no energy are set to 0.
+///   * input - Signal fed to the system. Should be broadband (e.g. white noise)
+///   * output - Signal measured at the system output
+///   * segment_len - Length of each segment
+///   * overlap - Number of samples shared by the consecutive segments
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::spectrums;
+/// 
+/// let x: Vec<f32> = (0..256).map(|i| ((i * i) % 17) as f32).collect();
+/// let y: Vec<f32> = x.iter().map(|v| -2.0 * v).collect();
+/// let h = spectrums::transfer_function(&x, &y, 64, 32);
+/// assert_approx_eq!(h[5].re, -2.0, 1e-4);
+/// assert_approx_eq!(h[5].im, 0.0, 1e-4);
+/// ```
+pub fn transfer_function(input: &RealBuffer, output: &RealBuffer, segment_len: usize, overlap: usize) -> ComplexBuffer {
+    let (pxx, _, pxy) = welch_cross_spectra(input, output, segment_len, overlap);
+    pxx.iter().zip(&pxy)
+        .map(|(&sxx, &sxy)| if sxx > 0.0 { sxy / sxx } else { Complex32::new(0.0, 0.0) })
+        .collect()
+}
+
+// Welch averaged auto and cross spectra (Pxx, Pyy, Pxy) with bins 0..=segment_len/2.
+// Spectra are not scaled, since they are used only in ratios.
+fn welch_cross_spectra(x: &RealBuffer, y: &RealBuffer, segment_len: usize, overlap: usize)
+        -> (RealBuffer, RealBuffer, ComplexBuffer) {
+    assert!(overlap < segment_len, "Overlap should be smaller than segment length");
+    let window = hann_periodic(segment_len, 0, segment_len);
+    let mut fft = ForwardFFT::new(segment_len);
+    let mut input = vec![Complex32::new(0.0, 0.0); segment_len];
+    let mut spectrum_x = vec![Complex32::new(0.0, 0.0); segment_len];
+    let mut spectrum_y = vec![Complex32::new(0.0, 0.0); segment_len];
+    let bins = segment_len / 2 + 1;
+    let mut pxx = vec![0.0; bins];
+    let mut pyy = vec![0.0; bins];
+    let mut pxy = vec![Complex32::new(0.0, 0.0); bins];
+    let len = usize::min(x.len(), y.len());
+    let hop = segment_len - overlap;
+    let mut start = 0;
+    while start + segment_len <= len {
+        for (signal, spectrum) in [(x, &mut spectrum_x), (y, &mut spectrum_y)].iter_mut() {
+            let segment = &signal[start..start + segment_len];
+            for ((c, &v), &w) in input.iter_mut().zip(segment).zip(window.samples()) {
+                *c = Complex32::new(v * w, 0.0);
+            }
+            fft.process(&mut input, spectrum);
+        }
+        for k in 0..bins {
+            pxx[k] += spectrum_x[k].norm_sqr();
+            pyy[k] += spectrum_y[k].norm_sqr();
+            pxy[k] += spectrum_x[k].conj() * spectrum_y[k];
+        }
+        start += hop;
+    }
+    (pxx, pyy, pxy)
+}
+
+/// Real cepstrum. Inverse FFT of the log magnitude spectrum.
+/// Periodic structures in spectrum (e.g. harmonics or echo) give peaks at their quefrency (in samples).
+/// https://en.wikipedia.org/wiki/Cepstrum
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::spectrums;
+/// 
+/// // Spectrum of the impulse is flat, so log magnitude is 0
+/// let c = spectrums::cepstrum(&vec![1.0, 0.0, 0.0, 0.0]);
+/// assert_approx_eq!(c[0], 0.0, 1e-6);
+/// ```
+pub fn cepstrum(signal: &RealBuffer) -> RealBuffer {
+    let n = signal.len();
+    let mut input: ComplexBuffer = signal.iter().map(|&v| Complex32::new(v, 0.0)).collect();
+    let mut spectrum = vec![Complex32::new(0.0, 0.0); n];
+    ForwardFFT::new(n).process(&mut input, &mut spectrum);
+    let mut log_spectrum: ComplexBuffer = spectrum.iter()
+        .map(|c| Complex32::new((c.norm() + LOG_FLOOR).ln(), 0.0))
+        .collect();
+    let mut output = vec![Complex32::new(0.0, 0.0); n];
+    InverseFFT::new(n).process(&mut log_spectrum, &mut output);
+    output.iter().map(|c| c.re / n as f32).collect()
+}
+
+/// Mel-frequency cepstral coefficients of the signal (treated as a single frame).
+/// 
+/// The signal is windowed with Hann window and its power spectrum is passed through
+/// num_mel_filters triangular filters spaced evenly on the mel scale between 0 Hz and Nyquist.
+/// The mel scale is mel(f) = 2595 * log10(1 + f/700).
+/// The first num_coeffs coefficients of the orthonormal DCT-II of the log filter energies are returned.
+/// Coefficient 0 is proportional to the log energy of the frame.
+/// 
+/// Panics if num_coeffs is greater than num_mel_filters.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::SourceNode;
+/// use dsp::generators::{GenNode, SineGen};
+/// use dsp::spectrums;
+/// 
+/// let mut gen = GenNode::new(Box::new(SineGen::new(440.0)), 16000.0, 512);
+/// let coeffs = spectrums::mfcc(gen.next_frame(), 16000.0, 13, 26);
+/// assert_eq!(coeffs.len(), 13);
+/// ```
+pub fn mfcc(signal: &RealBuffer, sample_rate: f32, num_coeffs: usize, num_mel_filters: usize) -> RealBuffer {
+    assert!(num_coeffs <= num_mel_filters, "Number of coefficients can't exceed number of mel filters");
+    let n = signal.len();
+    let window = hann_periodic(n, 0, n);
+    let mut input: ComplexBuffer = signal.iter().zip(window.samples())
+        .map(|(&v, &w)| Complex32::new(v * w, 0.0))
+        .collect();
+    let mut spectrum = vec![Complex32::new(0.0, 0.0); n];
+    ForwardFFT::new(n).process(&mut input, &mut spectrum);
+    let power: RealBuffer = spectrum.iter().take(n / 2 + 1).map(|c| c.norm_sqr()).collect();
+
+    let hz_to_mel = |f: f32| 2595.0 * (1.0 + f / 700.0).log10();
+    let mel_to_hz = |m: f32| 700.0 * (10f32.powf(m / 2595.0) - 1.0);
+    let max_mel = hz_to_mel(sample_rate / 2.0);
+    // Filter edges in (fractional) bins
+    let edges: RealBuffer = (0..num_mel_filters + 2)
+        .map(|i| mel_to_hz(max_mel * i as f32 / (num_mel_filters + 1) as f32) * n as f32 / sample_rate)
+        .collect();
+    let log_energies: RealBuffer = edges.windows(3)
+        .map(|e| {
+            let energy: f32 = power.iter().enumerate()
+                .map(|(k, p)| {
+                    let k = k as f32;
+                    let weight = if k > e[0] && k <= e[1] {
+                        (k - e[0]) / (e[1] - e[0])
+                    } else if k > e[1] && k < e[2] {
+                        (e[2] - k) / (e[2] - e[1])
+                    } else {
+                        0.0
+                    };
+                    weight * p
+                })
+                .sum();
+            (energy + LOG_FLOOR).ln()
+        })
+        .collect();
+    let mut coeffs = dct2_ortho(&log_energies);
+    coeffs.truncate(num_coeffs);
+    coeffs
+}
+
+/// Short-time Fourier transform.
+/// The window slides over the signal with the given hop size and each frame is transformed with FFT.
+/// The last frame is padded with zeros if it goes past the end of the signal.
+///   * window - Window applied to each frame. Its length is the frame size
+///   * hop - Number of samples between starts of the consecutive frames
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::{spectrums, windows};
+/// 
+/// let signal = vec![1.0; 10];
+/// let frames = spectrums::stft(&signal, &windows::rectangular(4, 0, 4), 4);
+/// assert_eq!(frames.len(), 3);
+/// assert_eq!(frames[2][0].re, 2.0);
+/// ```
+pub fn stft(signal: &RealBuffer, window: &Window, hop: usize) -> Vec<ComplexBuffer> {
+    assert!(hop > 0, "Hop size should be greater than 0");
+    let frame_size = window.len();
+    let mut fft = ForwardFFT::new(frame_size);
+    let mut frames = Vec::new();
+    let mut start = 0;
+    while start < signal.len() {
+        let mut input = vec![Complex32::new(0.0, 0.0); frame_size];
+        let end = usize::min(start + frame_size, signal.len());
+        for ((c, &v), &w) in input.iter_mut().zip(&signal[start..end]).zip(window.samples()) {
+            *c = Complex32::new(v * w, 0.0);
+        }
+        let mut output = vec![Complex32::new(0.0, 0.0); frame_size];
+        fft.process(&mut input, &mut output);
+        frames.push(output);
+        if start + frame_size >= signal.len() {
+            break;
+        }
+        start += hop;
+    }
+    frames
+}
+
+/// Overlap-add. Sums frames placed hop samples apart into a continuous signal.
+/// Inverse of framing done by stft, when the window satisfies COLA condition for the hop size
+/// (e.g. periodic Hann window with 50% overlap).
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::spectrums::overlap_add;
+/// 
+/// let frames = vec![vec![1.0, 1.0, 1.0, 1.0], vec![2.0, 2.0, 2.0, 2.0]];
+/// assert_eq!(overlap_add(&frames, 2), vec![1.0, 1.0, 3.0, 3.0, 2.0, 2.0]);
+/// ```
+pub fn overlap_add(frames: &[RealBuffer], hop: usize) -> RealBuffer {
+    let len = frames.iter()
+        .enumerate()
+        .map(|(i, frame)| i * hop + frame.len())
+        .max()
+        .unwrap_or(0);
+    let mut output = vec![0.0; len];
+    for (i, frame) in frames.iter().enumerate() {
+        for (y, x) in output[i * hop..].iter_mut().zip(frame) {
+            *y += x;
+        }
+    }
+    output
+}
+
+/// Goertzel algorithm. Calculates magnitude of a single frequency component.
+/// Faster than FFT, when only few frequencies are needed.
+/// Result is equal to the magnitude of DFT at the given frequency.
+/// https://en.wikipedia.org/wiki/Goertzel_algorithm
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::spectrums;
+/// 
+/// let signal = vec![1.0, 0.0, -1.0, 0.0];
+/// assert_approx_eq!(spectrums::goertzel(&signal, 1.0, 4.0), 2.0, 1e-5f32);
+/// assert_approx_eq!(spectrums::goertzel(&signal, 0.0, 4.0), 0.0, 1e-5f32);
+/// ```
+pub fn goertzel(signal: &RealBuffer, target_freq: f32, sample_rate: f32) -> f32 {
+    let w = 2.0 * std::f32::consts::PI * target_freq / sample_rate;
+    let coeff = 2.0 * w.cos();
+    let mut s1 = 0.0;
+    let mut s2 = 0.0;
+    for &x in signal {
+        let s = x + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s;
+    }
+    let power = s1 * s1 + s2 * s2 - coeff * s1 * s2;
+    power.max(0.0).sqrt()
+}
+
+/// Convert spectrum into magnitudes
+pub struct MagnitudeNode {
+    output: RealBuffer,
+}
+
+impl MagnitudeNode {
+    pub fn new(size: usize) -> MagnitudeNode {
+        MagnitudeNode { output: vec![0.0; size] }
+    }
+}
+
+impl ProcessingNode for MagnitudeNode {
+    type InBuffer = ComplexBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &ComplexBuffer) -> &RealBuffer {
+        for (out, c) in self.output.iter_mut().zip(input) {
+            *out = c.norm();
+        }
+        &self.output
+    }
+}
+
+
+/// Convert spectrum into phases
+pub struct PhaseNode {
+    output: RealBuffer,
+}
+
+impl PhaseNode {
+    pub fn new(size: usize) -> PhaseNode {
+        PhaseNode { output: vec![0.0; size] }
+    }
+}
+
+impl ProcessingNode for PhaseNode {
+    type InBuffer = ComplexBuffer;
+    type OutBuffer = RealBuffer;
+
+    fn process(&mut self, input: &ComplexBuffer) -> &RealBuffer {
+        for (out, c) in self.output.iter_mut().zip(input) {
+            *out = c.arg();
+        }
+        &self.output
+    }
+}
+
+
+/// ------------------------------------------------------------------------------------------------
+/// Module unit tests
+/// ------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::num_complex::Complex32;
+    use crate::{ProcessingNode, SourceNode};
+    use crate::fft::ForwardFFTNode;
+    use crate::generators::{GenNode, SineGen, ChirpGen, WhiteNoiseGen, PinkNoiseGen};
+    use crate::fft::irfft;
+    use crate::filters::{BiquadNode, biquad_lowpass};
+    use crate::windows::{hann, hann_periodic};
+    use super::*;
+
+    #[test]
+    fn test_magnitude_phase() {
+        let spectrum = vec![Complex32::new(1.0, 0.0),
+                            Complex32::new(0.0, 1.0),
+                            Complex32::new(-1.0, 0.0),
+                            Complex32::new(1.0, -1.0)];
+        let mags = magnitude(&spectrum);
+        let phases = phase(&spectrum);
+        let expected_mags = [1.0, 1.0, 1.0, f32::sqrt(2.0)];
+        let expected_phases = [0.0, PI / 2.0, PI, -PI / 4.0];
+        for i in 0..4 {
+            assert_approx_eq!(mags[i], expected_mags[i], 1e-5f32);
+            assert_approx_eq!(phases[i], expected_phases[i], 1e-5f32);
+        }
+        assert_eq!(MagnitudeNode::new(4).process(&spectrum), &mags);
+        assert_eq!(PhaseNode::new(4).process(&spectrum), &phases);
+    }
+
+    #[test]
+    fn test_magnitude_node_after_fft() {
+        let mut fft = ForwardFFTNode::new(4);
+        let mut mag_node = MagnitudeNode::new(4);
+        let signal = vec![1.0, 0.0, 0.0, 0.0];
+        let mags = mag_node.process(fft.process(&signal));
+        assert_eq!(mags, &vec![1.0; 4]);
+    }
+
+    #[test]
+    fn test_magnitude_db() {
+        let spectrum = vec![Complex32::new(2.0, 0.0),
+                            Complex32::new(0.0, 1.0),
+                            Complex32::new(0.0, 0.0)];
+        let db = magnitude_db(&spectrum, 2.0, -120.0);
+        assert_approx_eq!(db[0], 0.0, 1e-5f32);
+        assert_approx_eq!(db[1], -6.0206, 1e-3f32);
+        assert_eq!(db[2], -120.0);
+    }
+
+    #[test]
+    fn test_unwrap() {
+        // Linear phase ramp wrapped to [-π, π]
+        let ramp: Vec<f32> = (0..50).map(|i| 0.3 * i as f32).collect();
+        let wrapped: Vec<f32> = ramp.iter().map(|&p| Complex32::from_polar(&1.0, &p).arg()).collect();
+        let unwrapped = unwrap(&wrapped, PI);
+        for i in 0..50 {
+            assert_approx_eq!(unwrapped[i], ramp[i], 1e-3f32);
+        }
+        assert!(unwrapped.windows(2).all(|w| w[1] > w[0]));
+
+        // Decreasing phase
+        let ramp: Vec<f32> = ramp.iter().map(|p| -p).collect();
+        let wrapped: Vec<f32> = ramp.iter().map(|&p| Complex32::from_polar(&1.0, &p).arg()).collect();
+        let unwrapped = unwrap(&wrapped, PI);
+        for i in 0..50 {
+            assert_approx_eq!(unwrapped[i], ramp[i], 1e-3f32);
+        }
+    }
+
+    #[test]
+    fn test_welch_psd() {
+        let sample_rate = 1024.0;
+        let mut sine = GenNode::new(Box::new(SineGen::new(128.0)), sample_rate, 8192);
+        let mut noise = GenNode::new(Box::new(WhiteNoiseGen::new(11)), sample_rate, 8192);
+        let signal: RealBuffer = sine.next_frame().iter().zip(noise.next_frame())
+            .map(|(s, n)| 0.5 * s + n)
+            .collect();
+        let psd = welch_psd(&signal, 256, 128, &hann(256, 0, 256));
+        assert_eq!(psd.len(), 129);
+        // 128Hz with 4Hz per bin
+        let peak = (0..psd.len()).max_by(|&a, &b| psd[a].partial_cmp(&psd[b]).unwrap()).unwrap();
+        assert_eq!(peak, 32);
+        let mut sorted = psd.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let median = sorted[sorted.len() / 2];
+        assert!(psd[peak] > 20.0 * median);
+        // Uniform noise in [-1, 1] has variance 1/3. One-sided PSD is 2/3 per bin.
+        assert_approx_eq!(median, 2.0 / 3.0, 0.15);
+    }
+
+    #[test]
+    fn test_stft() {
+        let sample_rate = 1024.0;
+        let mut chirp = GenNode::new(Box::new(ChirpGen::new(50.0, 400.0, 2.0)), sample_rate, 2048);
+        let signal = chirp.next_frame();
+        let frames = stft(signal, &hann(256, 0, 256), 128);
+        assert_eq!(frames.len(), 15);
+        let peaks: Vec<usize> = frames.iter()
+            .map(|frame| vectors::argmax(&frame[0..128].to_vec()))
+            .collect();
+        assert!(peaks.windows(2).all(|w| w[1] > w[0]), "Peaks: {:?}", peaks);
+    }
+
+    #[test]
+    fn test_goertzel() {
+        let sample_rate = 8000.0;
+        let mut gen = GenNode::new(Box::new(SineGen::new(770.0)), sample_rate, 800);
+        let signal = gen.next_frame();
+        assert_approx_eq!(goertzel(signal, 770.0, sample_rate), 400.0, 1.0);
+        assert!(goertzel(signal, 1330.0, sample_rate) < 1.0);
+    }
+
+    #[test]
+    fn test_spectral_centroid() {
+        let mut spectrum = vec![Complex32::new(0.0, 0.0); 1024];
+        assert_eq!(spectral_centroid(&spectrum, 44100.0), 0.0);
+        spectrum[100] = Complex32::new(0.0, -3.0);
+        spectrum[1024 - 100] = Complex32::new(0.0, 3.0);
+        assert_approx_eq!(spectral_centroid(&spectrum, 1024.0), 100.0, 1e-5f32);
+    }
+
+    #[test]
+    fn test_spectral_flatness() {
+        let size = 1024;
+        let mut noise = GenNode::new(Box::new(WhiteNoiseGen::new(5)), size as f32, size);
+        let mut fft = ForwardFFTNode::new(size);
+        assert!(spectral_flatness(fft.process(noise.next_frame())) > 0.75);
+        let mut tone = GenNode::new(Box::new(SineGen::new(64.0)), size as f32, size);
+        assert!(spectral_flatness(fft.process(tone.next_frame())) < 0.01);
+        assert_eq!(spectral_flatness(&vec![Complex32::new(0.0, 0.0); size]), 0.0);
+    }
+
+    #[test]
+    fn test_spectral_rolloff() {
+        // Lowpass shaped spectrum falling off linearly up to bin 64 of 256
+        let size = 512;
+        let spectrum: ComplexBuffer = (0..size)
+            .map(|i| {
+                let bin = usize::min(i, size - i) as f32;
+                Complex32::new((1.0 - bin / 64.0).max(0.0).sqrt(), 0.0)
+            })
+            .collect();
+        // Energy below bin k is proportional to 1 - (1 - k/64)^2, so 75% is reached at bin 32
+        let rolloff = spectral_rolloff(&spectrum, size as f32, 0.75);
+        assert!((31.0..=33.0).contains(&rolloff));
+        assert!(spectral_rolloff(&spectrum, size as f32, 1.0) < 64.0);
+        assert_eq!(spectral_rolloff(&vec![Complex32::new(0.0, 0.0); size], 8.0, 0.85), 0.0);
+    }
+
+    #[test]
+    fn test_a_weighting() {
+        assert_approx_eq!(a_weighting_db(1000.0), 0.0, 0.01);
+        assert_approx_eq!(a_weighting_db(100.0), -19.1, 0.1);
+        assert_approx_eq!(a_weighting_db(10000.0), -2.5, 0.1);
+        let spectrum = vec![Complex32::new(1.0, 1.0); 100];
+        let weighted = a_weighting(&spectrum, 10000.0);
+        // Bins 1 and 99 are at 100 Hz and -100 Hz
+        assert_approx_eq!(20.0 * weighted[1].norm().log10(), 20.0 * 2f32.sqrt().log10() - 19.1, 0.1);
+        assert_eq!(weighted[1], weighted[99]);
+        assert_approx_eq!(weighted[10].re, 1.0, 1e-3);
+    }
+
+    #[test]
+    fn test_octave_bands() {
+        let size = 65536;
+        let sample_rate = 48000.0;
+        let mut gen = GenNode::new(Box::new(PinkNoiseGen::new(5)), sample_rate, size);
+        let mut fft = ForwardFFTNode::new(size);
+        let spectrum = fft.process(gen.next_frame()).clone();
+        let bands = octave_bands(&spectrum, sample_rate, 1);
+        assert_approx_eq!(bands.last().unwrap().0, 16000.0, 200.0);
+        // Pink noise has equal power per octave. Lowest bands are skipped since they contain only a few bins
+        let levels: RealBuffer = bands.iter().filter(|b| b.0 > 30.0).map(|b| 10.0 * b.1.log10()).collect();
+        let mean = levels.iter().sum::<f32>() / levels.len() as f32;
+        for level in &levels {
+            assert_approx_eq!(level, mean, 1.5);
+        }
+        // Three third-octave bands cover one octave band
+        let third_bands = octave_bands(&spectrum, sample_rate, 3);
+        let octave = bands.iter().position(|b| (b.0 - 1000.0).abs() < 1.0).unwrap();
+        let third = third_bands.iter().position(|b| (b.0 - 1000.0).abs() < 1.0).unwrap();
+        let third_power: f32 = third_bands[third - 1..=third + 1].iter().map(|b| b.1).sum();
+        assert_approx_eq!(third_power / bands[octave].1, 1.0, 1e-3);
+    }
+
+    #[test]
+    fn test_snr() {
+        let size = 1024;
+        let mut tone = GenNode::new(Box::new(SineGen::new(64.0)), size as f32, size);
+        let mut noise = GenNode::new(Box::new(WhiteNoiseGen::new(5)), size as f32, size);
+        let noise_frame: RealBuffer = noise.next_frame().iter().map(|v| 0.1 * v).collect();
+        let signal: RealBuffer = tone.next_frame().iter().zip(&noise_frame).map(|(a, b)| a + b).collect();
+        let noise_power = noise_frame.iter().map(|v| v * v).sum::<f32>() / size as f32;
+        // Sine with amplitude 1 has power 0.5
+        let expected = 10.0 * (0.5 / noise_power).log10();
+        let mut fft = ForwardFFTNode::new(size);
+        let spectrum = fft.process(&signal);
+        assert_approx_eq!(snr(spectrum, &[64]), expected, 0.1);
+        // Without harmonics SINAD differs from SNR only by the signal power counted in the total
+        let expected_sinad = 10.0 * ((0.5 + noise_power) / noise_power).log10();
+        assert_approx_eq!(sinad(spectrum, &[64]), expected_sinad, 0.1);
+    }
+
+    #[test]
+    fn test_find_peaks() {
+        let size = 1024;
+        let freq = 100.3;
+        let mut gen = GenNode::new(Box::new(SineGen::new(freq)), size as f32, size);
+        let mut windowed = vec![0.0; size];
+        hann(size, 0, size).apply(gen.next_frame(), &mut windowed);
+        let mut fft = ForwardFFTNode::new(size);
+        let spectrum = fft.process(&windowed);
+        let peaks = find_peaks(spectrum, size as f32, 3, 2.0);
+        assert_eq!(peaks.len(), 3);
+        // Other peaks are side lobes
+        assert!(peaks[1].1 < 0.01 * peaks[0].1);
+        let nearest_bin = vectors::argmax(spectrum) as f32;
+        assert!((peaks[0].0 - freq).abs() < (nearest_bin - freq).abs());
+        assert_approx_eq!(peaks[0].0, freq, 0.05);
+    }
+
+    #[test]
+    fn test_bin_frequencies() {
+        assert_eq!(bin_to_hz(0, 1024, 44100.0), 0.0);
+        assert_eq!(hz_to_bin(0.0, 1024, 44100.0), 0);
+        assert_approx_eq!(bin_to_hz(100, 1024, 44100.0), 4306.6406, 1e-3);
+        assert_eq!(hz_to_bin(4306.0, 1024, 44100.0), 100);
+        assert_eq!(bin_to_hz(512, 1024, 44100.0), 22050.0);
+        assert_eq!(hz_to_bin(22050.0, 1024, 44100.0), 512);
+        assert_eq!(hz_to_bin(-44100.0 / 1024.0, 1024, 44100.0), 1023);
+        assert_eq!(frequencies(4, 8.0), vec![0.0, 2.0, 4.0, -2.0]);
+        assert_eq!(frequencies(5, 10.0), vec![0.0, 2.0, 4.0, -4.0, -2.0]);
+    }
+
+    #[test]
+    fn test_thd() {
+        let sample_rate = 8000.0;
+        let mut fundamental = GenNode::new(Box::new(SineGen::new(500.0)), sample_rate, 800);
+        let mut harmonic = GenNode::new(Box::new(SineGen::new(1000.0)), sample_rate, 800);
+        let signal: RealBuffer = fundamental.next_frame().iter().zip(harmonic.next_frame())
+            .map(|(f, h)| f + 0.1 * h)
+            .collect();
+        let mut fft = ForwardFFTNode::new(800);
+        let spectrum = fft.process(&signal);
+        assert_approx_eq!(thd(spectrum, 500.0, sample_rate, 5), 0.1, 1e-3f32);
+    }
+
+    #[test]
+    fn test_thd_out_of_range() {
+        let spectrum = vec![Complex32::new(1.0, 0.0); 16];
+        assert_eq!(thd(&spectrum, 11.0, 16.0, 3), 0.0);
+        assert_eq!(thd(&spectrum, 8.0, 16.0, 3), 0.0);
+        assert!(thd(&spectrum, 4.0, 16.0, 3) > 0.0);
+        assert_eq!(thd(&spectrum, -4.0, 16.0, 3), 0.0);
+        assert_eq!(thd(&spectrum, 0.0, 16.0, 3), 0.0);
+        assert_eq!(thd(&vec![], 4.0, 16.0, 3), 0.0);
+    }
+
+    #[test]
+    fn test_stft_overlap_add() {
+        let signal = GenNode::new(Box::new(WhiteNoiseGen::new(2)), 1.0, 512).next_frame().clone();
+        let spectra = stft(&signal, &hann_periodic(64, 0, 64), 32);
+        let frames: Vec<RealBuffer> = spectra.iter()
+            .map(|spectrum| irfft(&spectrum[..33].to_vec(), 64))
+            .collect();
+        let output = overlap_add(&frames, 32);
+        assert_eq!(output.len(), 512);
+        // The first and the last half frame are not covered by two windows
+        for i in 32..480 {
+            assert_approx_eq!(output[i], signal[i], 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_coherence() {
+        let x = GenNode::new(Box::new(WhiteNoiseGen::new(1)), 1.0, 8192).next_frame().clone();
+        let y = GenNode::new(Box::new(WhiteNoiseGen::new(2)), 1.0, 8192).next_frame().clone();
+        let same = coherence(&x, &x, 256, 128);
+        assert_eq!(same.len(), 129);
+        for c in &same {
+            assert_approx_eq!(c, 1.0, 1e-4);
+        }
+        let independent = coherence(&x, &y, 256, 128);
+        assert!(independent.iter().all(|&c| (0.0..=1.0).contains(&c)));
+        let mean = independent.iter().sum::<f32>() / independent.len() as f32;
+        assert!(mean < 0.1);
+    }
+
+    #[test]
+    fn test_transfer_function_biquad() {
+        let (b, a) = biquad_lowpass(1000.0, 2.0, 8000.0);
+        let mut filter = BiquadNode::new(b, a, 16384);
+        let input = GenNode::new(Box::new(WhiteNoiseGen::new(3)), 1.0, 16384).next_frame().clone();
+        let output = filter.process(&input).clone();
+        let h = transfer_function(&input, &output, 256, 128);
+        assert_eq!(h.len(), 129);
+        for (k, estimate) in h.iter().enumerate() {
+            // Response of the biquad at z = e^(jw)
+            let z = Complex32::from_polar(&1.0, &(-2.0 * PI * k as f32 / 256.0));
+            let numerator = b[0] + b[1] * z + b[2] * z * z;
+            let denominator = a[0] + a[1] * z + a[2] * z * z;
+            let expected = numerator / denominator;
+            assert!((estimate - expected).norm() < 0.05 * f32::max(expected.norm(), 1.0));
+        }
+    }
+
+    #[test]
+    fn test_cepstrum_echo() {
+        let x = GenNode::new(Box::new(WhiteNoiseGen::new(4)), 1.0, 1024).next_frame().clone();
+        let signal: RealBuffer = (0..1024)
+            .map(|i| x[i] + if i >= 50 { 0.5 * x[i - 50] } else { 0.0 })
+            .collect();
+        let c = cepstrum(&signal);
+        assert_eq!(c.len(), 1024);
+        let peak = (20..512).max_by(|&a, &b| c[a].partial_cmp(&c[b]).unwrap()).unwrap();
+        assert_eq!(peak, 50);
+        // Log of zero spectrum is limited by the floor
+        assert!(cepstrum(&vec![0.0; 8]).iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn test_mfcc() {
+        let mut gen = GenNode::new(Box::new(SineGen::new(1000.0)), 16000.0, 512);
+        let tone = gen.next_frame().clone();
+        let coeffs = mfcc(&tone, 16000.0, 13, 26);
+        assert_eq!(coeffs.len(), 13);
+        let louder: RealBuffer = tone.iter().map(|v| 2.0 * v).collect();
+        assert!(mfcc(&louder, 16000.0, 13, 26)[0] > coeffs[0]);
+        let silence_coeffs = mfcc(&vec![0.0; 512], 16000.0, 13, 26);
+        assert!(coeffs[0] > silence_coeffs[0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mfcc_too_many_coeffs() {
+        mfcc(&vec![0.0; 512], 16000.0, 13, 8);
+    }
+
+    #[test]
+    fn test_mfcc_gain() {
+        let mut gen = GenNode::new(Box::new(WhiteNoiseGen::new(5)), 16000.0, 512);
+        let noise = gen.next_frame().clone();
+        let coeffs = mfcc(&noise, 16000.0, 13, 26);
+        // Scaling amplitude by 2 adds ln(4) to each log energy, so only coefficient 0 changes
+        let louder: RealBuffer = noise.iter().map(|v| 2.0 * v).collect();
+        let louder_coeffs = mfcc(&louder, 16000.0, 13, 26);
+        assert_approx_eq!(louder_coeffs[0] - coeffs[0], 26f32.sqrt() * 4f32.ln(), 1e-2);
+        for k in 1..13 {
+            assert_approx_eq!(louder_coeffs[k], coeffs[k], 1e-2);
+        }
+    }
+}
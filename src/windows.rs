@@ -2,9 +2,14 @@
 
 use std::cmp;
 use std::f32::consts::PI;
+use std::f64::consts::PI as PI64;
+use crate::num_complex::Complex64;
 use crate::{RealBuffer, ProcessingNode};
+use crate::fft::ForwardFFT;
 use crate::vectors;
 
+// Relative deviation below which overlapped windows are considered constant
+const COLA_TOLERANCE: f32 = 1e-4;
 
 /// A window function. Can be applied to a signal
 #[derive(Clone, Debug, PartialEq)]
@@ -18,6 +23,11 @@ impl Window {
         self.samples.len()
     }
 
+    /// Window coefficients
+    pub fn samples(&self) -> &RealBuffer {
+        &self.samples
+    }
+
     /// Apply this window to the given frame
     pub fn apply(&self, input: &RealBuffer, mut output: &mut RealBuffer) {
         vectors::multiply(&self.samples, &input, &mut output);
@@ -25,7 +35,78 @@ impl Window {
 }
 
 
+/// Window parameters used to scale spectrum estimates
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowStats {
+    /// Mean value of the window. Amplitude of the windowed tone is scaled by this value.
+    pub coherent_gain: f32,
+    /// Equivalent noise bandwidth in bins: N*sum(w^2)/sum(w)^2
+    pub enbw: f32,
+    /// Ratio of the coherent to the noise power gain. Equal to 1/enbw.
+    pub processing_gain: f32,
+}
+
+/// Calculate window statistics
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::windows;
+/// 
+/// let stats = windows::window_stats(&windows::hann(1000, 0, 1000));
+/// assert_approx_eq!(stats.coherent_gain, 0.5, 1e-3f32);
+/// assert_approx_eq!(stats.enbw, 1.5, 1e-2f32);
+/// ```
+pub fn window_stats(window: &Window) -> WindowStats {
+    let n = window.len() as f32;
+    let sum: f32 = window.samples.iter().sum();
+    let sum_squares: f32 = window.samples.iter().map(|w| w * w).sum();
+    let enbw = n * sum_squares / (sum * sum);
+    WindowStats { coherent_gain: sum / n, enbw, processing_gain: 1.0 / enbw }
+}
+
+/// Check constant overlap-add (COLA) condition.
+/// Windows shifted by hop samples should sum to a constant. Then STFT frames
+/// can be perfectly reconstructed with overlap-add.
+/// Returns whether the condition holds and the relative deviation (max - min) / mean of the sum.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::windows;
+/// 
+/// let win = windows::hann_periodic(64, 0, 64);
+/// assert!(windows::check_cola(win.samples(), 32).0);
+/// assert!(!windows::check_cola(win.samples(), 40).0);
+/// ```
+pub fn check_cola(window: &RealBuffer, hop: usize) -> (bool, f32) {
+    assert!(hop > 0, "Hop size should be greater than 0");
+    let mut sums = vec![0.0; hop];
+    for (i, w) in window.iter().enumerate() {
+        sums[i % hop] += w;
+    }
+    let min = sums.iter().cloned().fold(f32::INFINITY, f32::min);
+    let max = sums.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let mean = sums.iter().sum::<f32>() / hop as f32;
+    let deviation = if mean != 0.0 { (max - min) / mean.abs() } else { f32::INFINITY };
+    (deviation < COLA_TOLERANCE, deviation)
+}
+
+
 /// Window as a ProcessingNode
+/// If the input frame is shorter than the window, only the first input.len() samples are written.
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::ProcessingNode;
+/// use dsp::windows::{self, WindowNode};
+/// 
+/// let mut node = WindowNode::new(windows::triangular(5, 0, 5));
+/// let frame = vec![2.0; 5];
+/// assert_eq!(node.process(&frame), &vec![0.0, 1.0, 2.0, 1.0, 0.0]);
+/// ```
 pub struct WindowNode {
     window: Window,
     output: RealBuffer,
@@ -43,10 +124,13 @@ impl ProcessingNode for WindowNode {
     type OutBuffer = RealBuffer;
     
     fn process(&mut self, input: &RealBuffer) -> &RealBuffer {
-        self.window.apply(input, &mut self.output);
+        for ((out, x), w) in self.output.iter_mut().zip(input).zip(&self.window.samples) {
+            *out = x * w;
+        }
         &self.output
     }
 }
+
 /// Compute a simple rectangular window, a.k.a. __boxcar__ or __Dirichlet__ window
 /// 
 /// Example
@@ -88,11 +172,29 @@ pub fn triangular(width: usize, offset: usize, window_length: usize) -> Window {
     let slope = 2.0 / ((width - 1) as f32);
     for i in offset..end {
         let y = (i - offset) as f32 * slope;
-        samples[i] = if i - offset < window_length / 2 { y } else { 2.0 - y }    
+        samples[i] = if i - offset < width / 2 { y } else { 2.0 - y }    
     }
     Window { samples }
 }
 
+/// Create the Bartlett window. Same as triangular window, which is zero at both ends.
+/// https://en.wikipedia.org/wiki/Window_function#Triangular_window
+/// 
+/// Example
+/// 
+/// ```
+/// use dsp::windows;
+/// 
+/// let win = windows::bartlett(5, 1, 7);
+/// assert_eq!(win.samples(), &vec![0.0, 0.0, 0.5, 1.0, 0.5, 0.0, 0.0]);
+/// ```
+pub fn bartlett(width: usize, offset: usize, window_length: usize) -> Window {
+    if width <= 1 {
+        return rectangular(width, offset, window_length);
+    }
+    triangular(width, offset, window_length)
+}
+
 /// Create the Welch window
 /// https://en.wikipedia.org/wiki/Window_function#Welch_window
 /// 
@@ -172,16 +274,34 @@ pub fn sine(width: usize, offset: usize, window_length: usize) -> Window {
 /// assert_approx_eq!(output[6], 0.0, 1e-5f32);
 /// ```
 pub fn hann(width: usize, offset: usize, window_length: usize) -> Window {
-    let mut samples = vec![0.0; window_length];
-    let end = cmp::min(offset + width, window_length);
-    for i in offset..end {
-        let n = (i - offset) as f32;
-        samples[i] = (PI * n / (width - 1) as f32).sin().powi(2);
-    }
-    Window { samples }
+    cosine_sum(&[0.5, 0.5], (width - 1) as f32, width, offset, window_length)
+}
+
+/// Create the periodic Hann window.
+/// Periodic version is used for spectral analysis. It is the symmetric window
+/// of size width+1 without the last sample.
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::windows;
+/// 
+/// let win = windows::hann_periodic(4, 0, 4);
+/// let frame = vec![1.0; 4];
+/// let mut output = vec![0.0; 4];
+/// win.apply(&frame, &mut output);
+/// assert_approx_eq!(output[0], 0.0, 1e-5f32);
+/// assert_approx_eq!(output[1], 0.5, 1e-5f32);
+/// assert_approx_eq!(output[2], 1.0, 1e-5f32);
+/// assert_approx_eq!(output[3], 0.5, 1e-5f32);
+/// ```
+pub fn hann_periodic(width: usize, offset: usize, window_length: usize) -> Window {
+    cosine_sum(&[0.5, 0.5], width as f32, width, offset, window_length)
 }
 
 /// Compute a hamming window of the given size
+/// w[n] = 0.54 - 0.46*cos(2πn/(N-1))
 /// https://en.wikipedia.org/wiki/Window_function#Hann_and_Hamming_windows
 /// 
 /// Example
@@ -195,27 +315,24 @@ pub fn hann(width: usize, offset: usize, window_length: usize) -> Window {
 /// let mut output = vec![0.0; 7];
 /// win.apply(&frame, &mut output);
 /// assert_approx_eq!(output[0], 0.0, 1e-5f32);
-/// assert_approx_eq!(output[1], 0.0869, 1e-3f32);
-/// assert_approx_eq!(output[2], 0.54347825, 1e-3f32);
-/// assert_approx_eq!(output[3], 1.0, 1e-3f32);
-/// assert_approx_eq!(output[4], 0.54347825, 1e-3f32);
-/// assert_approx_eq!(output[5], 0.0869, 1e-3f32);
+/// assert_approx_eq!(output[1], 0.08, 1e-5f32);
+/// assert_approx_eq!(output[2], 0.54, 1e-5f32);
+/// assert_approx_eq!(output[3], 1.0, 1e-5f32);
+/// assert_approx_eq!(output[4], 0.54, 1e-5f32);
+/// assert_approx_eq!(output[5], 0.08, 1e-5f32);
 /// assert_approx_eq!(output[6], 0.0, 1e-5f32);
 /// ```
 pub fn hamming(width: usize, offset: usize, window_length: usize) -> Window {
-    let a0 = 25.0 / 46.0;
-    let mut samples = vec![0.0; window_length];
-    let end = cmp::min(offset + width, window_length);
-    let size = (width - 1) as f32;
-    for i in offset..end {
-        let n = (i - offset) as f32;
-        let v = a0 - (1.0 - a0) * (2.0 * PI * n / size).cos();
-        samples[i] = v;
-    }
-    Window { samples }
+    cosine_sum(&[0.54, 0.46], (width - 1) as f32, width, offset, window_length)
+}
+
+/// Compute the periodic hamming window. See hann_periodic.
+pub fn hamming_periodic(width: usize, offset: usize, window_length: usize) -> Window {
+    cosine_sum(&[0.54, 0.46], width as f32, width, offset, window_length)
 }
 
 /// Compute a Blackman window
+/// Uses "exact Blackman" coefficients, which give the highest side lobe at about -68 dB.
 /// https://en.wikipedia.org/wiki/Window_function#Blackman_window
 /// 
 /// Example
@@ -237,16 +354,275 @@ pub fn hamming(width: usize, offset: usize, window_length: usize) -> Window {
 /// assert_approx_eq!(output[6], 0.0, 1e-5f32);
 /// ```
 pub fn blackman(width: usize, offset: usize, window_length: usize) -> Window {
-    let a0 = 7938.0 / 18608.0;
-    let a1 = 9240.0 / 18608.0;
-    let a2 = 1430.0 / 18608.0;
+    let coeffs = [7938.0 / 18608.0, 9240.0 / 18608.0, 1430.0 / 18608.0];
+    cosine_sum(&coeffs, (width - 1) as f32, width, offset, window_length)
+}
+
+/// Compute a 4-term Blackman-Harris window
+/// The highest side lobe is at about -92 dB.
+/// https://en.wikipedia.org/wiki/Window_function#Blackman%E2%80%93Harris_window
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::windows;
+/// 
+/// let win = windows::blackman_harris(5, 1, 7);
+/// let frame = vec![1.0; 7];
+/// let mut output = vec![0.0; 7];
+/// win.apply(&frame, &mut output);
+/// assert_approx_eq!(output[0], 0.0, 1e-5f32);
+/// assert_approx_eq!(output[1], 0.00006, 1e-5f32);
+/// assert_approx_eq!(output[2], 0.21747, 1e-5f32);
+/// assert_approx_eq!(output[3], 1.0, 1e-5f32);
+/// assert_approx_eq!(output[4], 0.21747, 1e-5f32);
+/// assert_approx_eq!(output[5], 0.00006, 1e-5f32);
+/// assert_approx_eq!(output[6], 0.0, 1e-5f32);
+/// ```
+pub fn blackman_harris(width: usize, offset: usize, window_length: usize) -> Window {
+    let coeffs = [0.35875, 0.48829, 0.14128, 0.01168];
+    cosine_sum(&coeffs, (width - 1) as f32, width, offset, window_length)
+}
+
+/// Compute a flat top window
+/// Flat top window has very low scalloping loss (< 0.01 dB), so the amplitude of the tone
+/// can be measured accurately, even if its frequency is between FFT bins.
+/// https://en.wikipedia.org/wiki/Window_function#Flat_top_window
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::windows;
+/// 
+/// let win = windows::flattop(5, 1, 7);
+/// let frame = vec![1.0; 7];
+/// let mut output = vec![0.0; 7];
+/// win.apply(&frame, &mut output);
+/// assert_approx_eq!(output[0], 0.0, 1e-5f32);
+/// assert_approx_eq!(output[1], -0.00042, 1e-5f32);
+/// assert_approx_eq!(output[2], -0.05474, 1e-5f32);
+/// assert_approx_eq!(output[3], 1.0, 1e-5f32);
+/// assert_approx_eq!(output[4], -0.05474, 1e-5f32);
+/// assert_approx_eq!(output[5], -0.00042, 1e-5f32);
+/// assert_approx_eq!(output[6], 0.0, 1e-5f32);
+/// ```
+pub fn flattop(width: usize, offset: usize, window_length: usize) -> Window {
+    let coeffs = [0.21557895, 0.41663158, 0.27726316, 0.08357895, 0.006947368];
+    cosine_sum(&coeffs, (width - 1) as f32, width, offset, window_length)
+}
+
+/// Compute a Dolph-Chebyshev window.
+/// All side lobes have equal level at_db decibels below the main lobe.
+/// The window is designed in frequency domain and transformed with FFT.
+/// https://en.wikipedia.org/wiki/Window_function#Dolph%E2%80%93Chebyshev_window
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::windows;
+/// 
+/// let win = windows::chebyshev(7, 0, 7, 50.0);
+/// let samples = win.samples();
+/// assert_approx_eq!(samples[3], 1.0, 1e-6f32);
+/// assert_approx_eq!(samples[0], samples[6], 1e-6f32);
+/// assert!(samples[0] < samples[1] && samples[1] < samples[2]);
+/// ```
+pub fn chebyshev(width: usize, offset: usize, window_length: usize, at_db: f32) -> Window {
+    let mut samples = vec![0.0; window_length];
+    let end = cmp::min(offset + width, window_length);
+    if width <= 1 {
+        return rectangular(width, offset, window_length);
+    }
+    let m = width as f64;
+    let order = m - 1.0;
+    let beta = ((10f64.powf(at_db as f64 / 20.0)).acosh() / order).cosh();
+    // Chebyshev polynomial of the given order evaluated at the frequency samples
+    let mut spectrum: Vec<Complex64> = (0..width)
+        .map(|k| {
+            let x = beta * (PI64 * k as f64 / m).cos();
+            let p = if x > 1.0 {
+                (order * x.acosh()).cosh()
+            } else if x < -1.0 {
+                let sign = if width % 2 == 1 { 1.0 } else { -1.0 };
+                sign * (order * (-x).acosh()).cosh()
+            } else {
+                (order * x.acos()).cos()
+            };
+            if width % 2 == 1 {
+                Complex64::new(p, 0.0)
+            } else {
+                // Half sample shift makes the even length window symmetric
+                Complex64::from_polar(&p, &(PI64 * k as f64 / m))
+            }
+        })
+        .collect();
+    let mut time = vec![Complex64::new(0.0, 0.0); width];
+    ForwardFFT::new(width).process(&mut spectrum, &mut time);
+    // FFT output starts at the window center. Mirror it to get the full window
+    let center = width / 2;
+    let values: Vec<f64> = (0..width)
+        .map(|i| {
+            let k = if i < center {
+                center - i
+            } else if width % 2 == 1 {
+                i - center
+            } else {
+                i - center + 1
+            };
+            time[k].re
+        })
+        .collect();
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    for (sample, v) in samples[offset.min(end)..end].iter_mut().zip(&values) {
+        *sample = (v / max) as f32;
+    }
+    Window { samples }
+}
+
+/// Compute a Gaussian window
+/// w[n] = exp(-0.5 * ((n - M) / (sigma * M))^2), M = (N-1)/2
+/// Smaller sigma gives narrower window (more energy in the center).
+/// https://en.wikipedia.org/wiki/Window_function#Gaussian_window
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::windows;
+/// 
+/// let win = windows::gaussian(5, 1, 7, 0.5);
+/// let samples = win.samples();
+/// assert_approx_eq!(samples[0], 0.0, 1e-5f32);
+/// assert_approx_eq!(samples[1], 0.13534, 1e-5f32);
+/// assert_approx_eq!(samples[2], 0.60653, 1e-5f32);
+/// assert_approx_eq!(samples[3], 1.0, 1e-5f32);
+/// ```
+pub fn gaussian(width: usize, offset: usize, window_length: usize, sigma: f32) -> Window {
+    if width <= 1 {
+        return rectangular(width, offset, window_length);
+    }
+    let mut samples = vec![0.0; window_length];
+    let end = cmp::min(offset + width, window_length);
+    let center = (width - 1) as f32 / 2.0;
+    for (n, sample) in samples[offset.min(end)..end].iter_mut().enumerate() {
+        let x = (n as f32 - center) / (sigma * center);
+        *sample = (-0.5 * x * x).exp();
+    }
+    Window { samples }
+}
+
+/// Compute a Kaiser window
+/// Beta parameter controls trade-off between main lobe width and side lobe level.
+/// beta = 0 gives rectangular window. Larger beta gives lower side lobes and wider main lobe.
+/// https://en.wikipedia.org/wiki/Kaiser_window
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::windows;
+/// 
+/// let win = windows::kaiser(5, 1, 7, 5.0);
+/// let frame = vec![1.0; 7];
+/// let mut output = vec![0.0; 7];
+/// win.apply(&frame, &mut output);
+/// assert_approx_eq!(output[0], 0.0, 1e-5f32);
+/// assert_approx_eq!(output[1], 0.03671, 1e-5f32);
+/// assert_approx_eq!(output[2], 0.55285, 1e-5f32);
+/// assert_approx_eq!(output[3], 1.0, 1e-5f32);
+/// assert_approx_eq!(output[4], 0.55285, 1e-5f32);
+/// assert_approx_eq!(output[5], 0.03671, 1e-5f32);
+/// assert_approx_eq!(output[6], 0.0, 1e-5f32);
+/// ```
+pub fn kaiser(width: usize, offset: usize, window_length: usize, beta: f32) -> Window {
+    let mut samples = vec![0.0; window_length];
+    let end = cmp::min(offset + width, window_length);
+    let size = (width - 1) as f64;
+    let beta = beta as f64;
+    let denom = bessel_i0(beta);
+    for (n, sample) in samples[offset.min(end)..end].iter_mut().enumerate() {
+        let x = 2.0 * n as f64 / size - 1.0;
+        *sample = (bessel_i0(beta * (1.0 - x * x).max(0.0).sqrt()) / denom) as f32;
+    }
+    Window { samples }
+}
+
+/// Zeroth order modified Bessel function of the first kind
+/// Calculated from the power series: I0(x) = sum ((x/2)^k / k!)^2
+fn bessel_i0(x: f64) -> f64 {
+    let half_x = x / 2.0;
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > 1e-12 * sum {
+        term *= (half_x / k).powi(2);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Compute a Tukey (tapered cosine) window
+/// Central (1 - alpha) part of the window is flat. The edges are tapered with cosine.
+/// alpha = 0 gives rectangular window and alpha = 1 gives Hann window.
+/// https://en.wikipedia.org/wiki/Window_function#Tukey_window
+/// 
+/// Example
+/// 
+/// ```
+/// use assert_approx_eq::assert_approx_eq;
+/// use dsp::windows;
+/// 
+/// let win = windows::tukey(5, 1, 7, 0.5);
+/// let frame = vec![1.0; 7];
+/// let mut output = vec![0.0; 7];
+/// win.apply(&frame, &mut output);
+/// assert_approx_eq!(output[0], 0.0, 1e-5f32);
+/// assert_approx_eq!(output[1], 0.0, 1e-5f32);
+/// assert_approx_eq!(output[2], 1.0, 1e-5f32);
+/// assert_approx_eq!(output[3], 1.0, 1e-5f32);
+/// assert_approx_eq!(output[4], 1.0, 1e-5f32);
+/// assert_approx_eq!(output[5], 0.0, 1e-5f32);
+/// assert_approx_eq!(output[6], 0.0, 1e-5f32);
+/// ```
+pub fn tukey(width: usize, offset: usize, window_length: usize, alpha: f32) -> Window {
+    let alpha = alpha.clamp(0.0, 1.0);
+    if alpha == 0.0 {
+        return rectangular(width, offset, window_length);
+    }
     let mut samples = vec![0.0; window_length];
     let end = cmp::min(offset + width, window_length);
     let size = (width - 1) as f32;
+    let taper = alpha * size / 2.0;
+    for (n, sample) in samples[offset.min(end)..end].iter_mut().enumerate() {
+        // Distance from the nearest edge
+        let d = f32::min(n as f32, size - n as f32);
+        *sample = if d < taper {
+            0.5 * (1.0 - (PI * d / taper).cos())
+        } else {
+            1.0
+        };
+    }
+    Window { samples }
+}
+
+/// Generalized cosine window
+/// w[n] = a0 - a1*cos(2πn/size) + a2*cos(4πn/size) - ...
+///   * size - width-1 for symmetric window and width for periodic
+fn cosine_sum(coeffs: &[f32], size: f32, width: usize, offset: usize, window_length: usize) -> Window {
+    let mut samples = vec![0.0; window_length];
+    let end = cmp::min(offset + width, window_length);
     for i in offset..end {
         let n = (i - offset) as f32;
-        let v = a0 - a1 * (2.0 * PI * n / size).cos()
-                    + a2 * (4.0 * PI * n / size).cos();
+        let mut sign = 1.0;
+        let mut v = 0.0;
+        for (k, a) in coeffs.iter().enumerate() {
+            v += sign * a * (2.0 * PI * k as f32 * n / size).cos();
+            sign = -sign;
+        }
         samples[i] = v;
     }
     Window { samples }
@@ -259,6 +635,9 @@ pub fn blackman(width: usize, offset: usize, window_length: usize) -> Window {
 mod tests {
 
     use assert_approx_eq::assert_approx_eq;
+    use crate::SourceNode;
+    use crate::generators::{GenNode, DCGen, SineGen};
+    use crate::fft::{ForwardFFTNode, rfft};
     use super::*;
 
     #[test]
@@ -280,4 +659,215 @@ mod tests {
         let area: f32 = output.iter().sum();
         assert_approx_eq!(area / 1000.0, 0.5, 0.2);
     }
+
+    #[test]
+    fn test_hann() {
+        let win = hann(101, 0, 101);
+        let samples = win.samples();
+        assert_approx_eq!(samples[0], 0.0, 1e-6);
+        assert_approx_eq!(samples[50], 1.0, 1e-6);
+        assert_approx_eq!(samples[100], 0.0, 1e-6);
+
+        let win = hann_periodic(100, 0, 100);
+        let samples = win.samples();
+        assert_approx_eq!(samples[0], 0.0, 1e-6);
+        assert_approx_eq!(samples[50], 1.0, 1e-6);
+        assert_approx_eq!(samples[99], samples[1], 1e-6);
+    }
+
+    #[test]
+    fn test_hamming() {
+        let size = 64;
+        let win = hamming(size, 0, size);
+        let samples = win.samples();
+        assert_approx_eq!(samples[0], 0.08, 1e-6);
+        assert_approx_eq!(samples[size-1], 0.08, 1e-6);
+        let peak = samples.iter().cloned().fold(0.0, f32::max);
+        assert_approx_eq!(peak, 1.0, 1e-3);
+
+        let frame = vec![2.0; size];
+        let mut output = vec![0.0; size];
+        win.apply(&frame, &mut output);
+        let n = size as f32;
+        let sum: f32 = output.iter().sum();
+        let energy: f32 = output.iter().map(|x| x * x).sum();
+        assert_approx_eq!(sum, 2.0 * (0.54 * n - 0.46), 1e-3);
+        let window_energy = 0.54f32.powi(2) * n - 2.0 * 0.54 * 0.46 + 0.46f32.powi(2) * ((n - 1.0) / 2.0 + 1.0);
+        assert_approx_eq!(energy, 4.0 * window_energy, 1e-3);
+
+        let win = hamming_periodic(size, 0, size);
+        assert_approx_eq!(win.samples()[size / 2], 1.0, 1e-6);
+    }
+
+    #[test]
+    fn test_blackman_symmetry() {
+        for win in [blackman(33, 0, 33), blackman_harris(33, 0, 33)].iter() {
+            let samples = win.samples();
+            for i in 0..33 {
+                assert_approx_eq!(samples[i], samples[32 - i], 1e-5);
+            }
+            assert_approx_eq!(samples[16], 1.0, 1e-5);
+        }
+        // Reference values for N=9 (angle π/4 per sample)
+        let samples = blackman_harris(9, 0, 9).samples().clone();
+        assert_approx_eq!(samples[2], 0.21747, 1e-5);
+        assert_approx_eq!(samples[1], 0.021736, 1e-5);
+    }
+
+    #[test]
+    fn test_kaiser() {
+        assert_eq!(kaiser(16, 0, 16, 0.0), rectangular(16, 0, 16));
+        let win = kaiser(33, 0, 33, 8.6);
+        let samples = win.samples();
+        let peak = (0..33).max_by(|&a, &b| samples[a].partial_cmp(&samples[b]).unwrap()).unwrap();
+        assert_eq!(peak, 16);
+        assert_approx_eq!(samples[16], 1.0, 1e-6);
+        // Larger beta tapers more
+        assert!(kaiser(33, 0, 33, 2.0).samples()[0] > samples[0]);
+        assert_approx_eq!(bessel_i0(1.0) as f32, 1.266066, 1e-6);
+    }
+
+    #[test]
+    fn test_window_node() {
+        let mut gen = GenNode::new(Box::new(DCGen::new(1.0)), 64.0, 64);
+        let mut node = WindowNode::new(hann(64, 0, 64));
+        let output = node.process(gen.next_frame());
+        assert_approx_eq!(output[0], 0.0, 1e-6);
+        assert_approx_eq!(output[63], 0.0, 1e-6);
+        assert!(output[1] < output[8] && output[8] < output[31]);
+        assert!(output[62] < output[55] && output[55] < output[32]);
+
+        // Shorter input only updates the first samples
+        let win = hann(64, 0, 64);
+        let output = node.process(&vec![1.0; 3]);
+        assert_eq!(output.len(), 64);
+        assert_approx_eq!(output[2], win.samples()[2], 1e-6);
+    }
+
+    #[test]
+    fn test_tukey() {
+        assert_eq!(tukey(101, 0, 101, 0.0), rectangular(101, 0, 101));
+        let win = tukey(101, 0, 101, 1.0);
+        let expected = hann(101, 0, 101);
+        for i in 0..101 {
+            assert_approx_eq!(win.samples()[i], expected.samples()[i], 1e-5);
+        }
+        let win = tukey(101, 0, 101, 0.5);
+        let samples = win.samples();
+        let flat = samples.iter().filter(|&&w| w == 1.0).count();
+        assert_eq!(flat, 51);
+        assert_eq!(samples[0], 0.0);
+        assert_approx_eq!(samples[12], 0.5 * (1.0 - (PI * 12.0 / 25.0).cos()), 1e-6);
+        assert!(samples[..25].windows(2).all(|w| w[0] < w[1]));
+        assert!(samples[76..].windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn test_flattop_amplitude() {
+        let size = 256;
+        let win = flattop(size, 0, size);
+        let gain: f32 = win.samples().iter().sum();
+        let mut fft = ForwardFFTNode::new(size);
+        let mut windowed = vec![0.0; size];
+        for &bin_offset in [0.0, 0.25, 0.5, 0.75].iter() {
+            let freq = 20.0 + bin_offset;
+            let mut gen = GenNode::new(Box::new(SineGen::new(freq)), size as f32, size);
+            win.apply(gen.next_frame(), &mut windowed);
+            let spectrum = fft.process(&windowed);
+            let peak = spectrum[..size/2].iter().map(|c| c.norm()).fold(0.0, f32::max);
+            let amplitude = 2.0 * peak / gain;
+            assert_approx_eq!(amplitude, 1.0, 0.01);
+        }
+    }
+
+    #[test]
+    fn test_window_stats() {
+        let stats = window_stats(&rectangular(64, 0, 64));
+        assert_eq!(stats.enbw, 1.0);
+        assert_eq!(stats.coherent_gain, 1.0);
+        assert_eq!(stats.processing_gain, 1.0);
+        let stats = window_stats(&hann_periodic(64, 0, 64));
+        assert_approx_eq!(stats.coherent_gain, 0.5, 1e-5);
+        assert_approx_eq!(stats.enbw, 1.5, 1e-5);
+        assert_approx_eq!(stats.processing_gain, 1.0 / 1.5, 1e-5);
+    }
+
+    #[test]
+    fn test_check_cola() {
+        let win = hann_periodic(64, 0, 64);
+        let (ok, deviation) = check_cola(win.samples(), 32);
+        assert!(ok);
+        assert!(deviation < 1e-5);
+        assert!(check_cola(win.samples(), 16).0);
+        let (ok, deviation) = check_cola(win.samples(), 27);
+        assert!(!ok);
+        assert!(deviation > 0.01);
+        assert!(check_cola(rectangular(8, 0, 8).samples(), 8).0);
+    }
+
+    #[test]
+    fn test_chebyshev_sidelobes() {
+        for &(width, at_db) in &[(63, 60.0), (64, 80.0), (31, 40.0)] {
+            let mut padded = chebyshev(width, 0, width, at_db).samples().clone();
+            for i in 0..width / 2 {
+                assert_approx_eq!(padded[i], padded[width - 1 - i], 1e-6);
+            }
+            padded.resize(4096, 0.0);
+            let db: RealBuffer = rfft(&padded).iter().map(|c| 20.0 * c.norm().log10()).collect();
+            // Skip the main lobe up to the first minimum
+            let first_null = (1..db.len() - 1).find(|&k| db[k] < db[k - 1] && db[k] <= db[k + 1]).unwrap();
+            let sidelobe = db[first_null..].iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            assert_approx_eq!(sidelobe - db[0], -at_db, 0.5);
+        }
+    }
+
+    #[test]
+    fn test_triangular_short() {
+        assert_eq!(triangular(3, 0, 8).samples(), &vec![0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        let even = triangular(4, 0, 10);
+        for (x, y) in even.samples().iter().zip(&[0.0, 2.0 / 3.0, 2.0 / 3.0, 0.0, 0.0]) {
+            assert_approx_eq!(x, y, 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_bartlett() {
+        for &width in &[9, 10] {
+            let win = bartlett(width, 0, width);
+            let samples = win.samples();
+            assert_eq!(samples[0], 0.0);
+            assert_eq!(samples[width - 1], 0.0);
+            let slope = samples[1] - samples[0];
+            assert_approx_eq!(slope, 2.0 / (width - 1) as f32, 1e-6);
+            for i in 1..width / 2 {
+                assert_approx_eq!(samples[i] - samples[i - 1], slope, 1e-6);
+                assert_approx_eq!(samples[width - 1 - i] - samples[width - i], slope, 1e-6);
+            }
+        }
+        assert_eq!(bartlett(9, 0, 9).samples()[4], 1.0);
+        assert_eq!(bartlett(4, 10, 8).samples(), &vec![0.0; 8]);
+    }
+
+    #[test]
+    fn test_gaussian() {
+        let win = gaussian(33, 0, 33, 0.4);
+        let samples = win.samples();
+        assert_eq!(samples[16], 1.0);
+        for i in 0..16 {
+            assert_approx_eq!(samples[i], samples[32 - i], 1e-6);
+            assert!(samples[i] < samples[i + 1]);
+        }
+        // Smaller sigma concentrates energy in the center
+        let narrow = gaussian(33, 0, 33, 0.2);
+        assert!(narrow.samples()[8] < samples[8]);
+        assert!(window_stats(&narrow).coherent_gain < window_stats(&win).coherent_gain);
+    }
+
+    #[test]
+    fn test_window_offset_past_end() {
+        assert_eq!(kaiser(4, 10, 8, 5.0).samples(), &vec![0.0; 8]);
+        assert_eq!(tukey(4, 10, 8, 0.5).samples(), &vec![0.0; 8]);
+        assert_eq!(chebyshev(4, 10, 8, 60.0).samples(), &vec![0.0; 8]);
+        assert_eq!(gaussian(4, 10, 8, 0.4).samples(), &vec![0.0; 8]);
+    }
 }